@@ -0,0 +1,769 @@
+//! A compact binary encoding that can be read in place, without
+//! deserializing the tree.
+//!
+//! The buffer starts with the four byte [`MAGIC`] followed by the root node.
+//! Nodes are laid out in preorder, each made of:
+//!
+//! - the length of the node, its descendants included
+//! - the length of the value, followed by the encoded value
+//! - the number of children
+//! - a table with the offset of each child record, relative to the start of
+//!   the node
+//! - the child records, each being the length of the key, the encoded key and
+//!   the child node
+//!
+//! Lengths, counts and offsets are little endian `u32`s. Children are sorted
+//! by their encoded keys, so [`ArchivedTree::get_child`] can binary search
+//! them. The [`Encode`] implementations for integers preserve their order, so
+//! the children of an archived tree come in the same order as in the tree.
+//!
+//! ```
+//! use tagged_tree::{binary::ArchivedTree, Tree};
+//!
+//! let mut tree = Tree::new(0u32);
+//! tree.entry("a".to_owned()).or_insert(1).add_child("b".to_owned(), 2);
+//! let bytes = tree.to_binary().unwrap();
+//!
+//! let archived = ArchivedTree::<String, u32>::new(&bytes).unwrap();
+//! assert_eq!(archived.get_path(["a", "b"]).unwrap().value(), 2);
+//! assert_eq!(archived.to_tree(), tree);
+//! ```
+
+use crate::Tree;
+use std::{
+    error::Error, fmt, io, iter::FusedIterator, marker::PhantomData, str,
+};
+
+/// The bytes every encoded tree starts with
+pub const MAGIC: &[u8; 4] = b"TTR\x01";
+
+/// The smallest possible node: its length, an empty value and no children
+const MIN_NODE_LEN: usize = 12;
+
+/// Types that can be written as keys and values
+pub trait Encode {
+    fn encode(&self, out: &mut Vec<u8>);
+}
+
+/// Types that can be read back from their [`Encode`]d bytes
+pub trait Decode<'a>: Sized {
+    fn decode(bytes: &'a [u8]) -> Result<Self, DecodeError>;
+}
+
+/// The error returned when reading malformed bytes
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DecodeError {
+    message: &'static str,
+}
+
+impl DecodeError {
+    #[inline]
+    pub fn new(message: &'static str) -> Self {
+        DecodeError { message }
+    }
+
+    #[inline]
+    pub fn message(&self) -> &'static str {
+        self.message
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid binary tree: {}", self.message)
+    }
+}
+
+impl Error for DecodeError {}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        (**self).encode(out)
+    }
+}
+
+impl Encode for () {
+    #[inline]
+    fn encode(&self, _: &mut Vec<u8>) {}
+}
+
+impl Decode<'_> for () {
+    #[inline]
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        if bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(DecodeError::new("unexpected bytes for a unit value"))
+        }
+    }
+}
+
+impl Encode for bool {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8)
+    }
+}
+
+impl Decode<'_> for bool {
+    #[inline]
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        match bytes {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(DecodeError::new("invalid bool")),
+        }
+    }
+}
+
+impl Encode for char {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u32).encode(out)
+    }
+}
+
+impl Decode<'_> for char {
+    #[inline]
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        char::from_u32(u32::decode(bytes)?)
+            .ok_or_else(|| DecodeError::new("invalid char"))
+    }
+}
+
+impl Encode for str {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.as_bytes())
+    }
+}
+
+impl Encode for String {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_str().encode(out)
+    }
+}
+
+impl<'a> Decode<'a> for &'a str {
+    #[inline]
+    fn decode(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        str::from_utf8(bytes).map_err(|_| DecodeError::new("invalid UTF-8"))
+    }
+}
+
+impl Decode<'_> for String {
+    #[inline]
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        <&str>::decode(bytes).map(str::to_owned)
+    }
+}
+
+impl Encode for [u8] {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self)
+    }
+}
+
+impl Encode for Vec<u8> {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_slice().encode(out)
+    }
+}
+
+impl<'a> Decode<'a> for &'a [u8] {
+    #[inline]
+    fn decode(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        Ok(bytes)
+    }
+}
+
+impl Decode<'_> for Vec<u8> {
+    #[inline]
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(bytes.to_vec())
+    }
+}
+
+/// Unsigned integers are big endian, so their bytes sort like the numbers
+macro_rules! unsigned {
+    ($($int:ty)*) => {$(
+        impl Encode for $int {
+            #[inline]
+            fn encode(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_be_bytes())
+            }
+        }
+
+        impl Decode<'_> for $int {
+            #[inline]
+            fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+                let mut word = [0; std::mem::size_of::<$int>()];
+                if bytes.len() != word.len() {
+                    return Err(DecodeError::new("invalid integer length"));
+                }
+                word.copy_from_slice(bytes);
+                Ok(<$int>::from_be_bytes(word))
+            }
+        }
+    )*};
+}
+
+/// Signed integers have their sign bit flipped, so negative numbers sort
+/// before positive ones
+macro_rules! signed {
+    ($($int:ty => $unsigned:ty)*) => {$(
+        impl Encode for $int {
+            #[inline]
+            fn encode(&self, out: &mut Vec<u8>) {
+                ((*self as $unsigned) ^ !(<$unsigned>::MAX >> 1)).encode(out)
+            }
+        }
+
+        impl Decode<'_> for $int {
+            #[inline]
+            fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+                let bits = <$unsigned>::decode(bytes)?;
+                Ok((bits ^ !(<$unsigned>::MAX >> 1)) as $int)
+            }
+        }
+    )*};
+}
+
+unsigned!(u8 u16 u32 u64 u128);
+signed!(i8 => u8 i16 => u16 i32 => u32 i64 => u64 i128 => u128);
+
+impl Encode for usize {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u64).encode(out)
+    }
+}
+
+impl Decode<'_> for usize {
+    #[inline]
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let value = u64::decode(bytes)?;
+        if value > usize::MAX as u64 {
+            return Err(DecodeError::new("integer doesn't fit in a usize"));
+        }
+        Ok(value as usize)
+    }
+}
+
+impl Encode for isize {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as i64).encode(out)
+    }
+}
+
+impl Decode<'_> for isize {
+    #[inline]
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let value = i64::decode(bytes)?;
+        if value < isize::MIN as i64 || value > isize::MAX as i64 {
+            return Err(DecodeError::new("integer doesn't fit in an isize"));
+        }
+        Ok(value as isize)
+    }
+}
+
+impl<K: Ord + Encode, V: Encode> Tree<K, V> {
+    /// Encodes the tree in the binary format
+    ///
+    /// Fails if the tree doesn't fit in the `u32` offsets, or if two keys
+    /// have the same encoding.
+    #[inline]
+    pub fn to_binary(&self) -> io::Result<Vec<u8>> {
+        encode(self)
+    }
+
+    /// Writes the tree in the binary format
+    pub fn write_binary<W: io::Write>(&self, mut out: W) -> io::Result<()> {
+        out.write_all(&self.to_binary()?)
+    }
+}
+
+/// A node about to be encoded
+#[cfg_attr(not(feature = "mmap"), allow(dead_code))]
+pub(crate) enum Parts<'s, S> {
+    /// A node that is already encoded, and is copied as is
+    Encoded(&'s [u8]),
+    Node {
+        value: Vec<u8>,
+        children: Vec<(Vec<u8>, S)>,
+    },
+}
+
+/// Something that can be encoded like a tree
+pub(crate) trait Source<'s>: Sized {
+    fn parts(self) -> Parts<'s, Self>;
+}
+
+impl<'t, K: Ord + Encode, V: Encode> Source<'t> for &'t Tree<K, V> {
+    fn parts(self) -> Parts<'t, Self> {
+        let mut value = Vec::new();
+        self.value.encode(&mut value);
+        let children = self
+            .children
+            .iter()
+            .map(|(key, child)| {
+                let mut bytes = Vec::new();
+                key.encode(&mut bytes);
+                (bytes, child)
+            })
+            .collect();
+        Parts::Node { value, children }
+    }
+}
+
+/// Where encoded trees are written
+pub(crate) trait Output {
+    fn position(&self) -> usize;
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()>;
+
+    /// Overwrites four bytes that were already written
+    fn patch(&mut self, at: usize, bytes: [u8; 4]) -> io::Result<()>;
+}
+
+impl Output for Vec<u8> {
+    #[inline]
+    fn position(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    #[inline]
+    fn patch(&mut self, at: usize, bytes: [u8; 4]) -> io::Result<()> {
+        self[at..at + 4].copy_from_slice(&bytes);
+        Ok(())
+    }
+}
+
+/// Encodes a whole tree, without recursing
+pub(crate) fn encode<'s, S: Source<'s>>(root: S) -> io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    encode_into(root, &mut out)?;
+    Ok(out)
+}
+
+pub(crate) fn encode_into<'s, S, O>(root: S, out: &mut O) -> io::Result<()>
+where
+    S: Source<'s>,
+    O: Output,
+{
+    out.write(MAGIC)?;
+    let mut encoder = Encoder {
+        out,
+        stack: Vec::new(),
+    };
+    encoder.begin(root)?;
+    while let Some(frame) = encoder.stack.last_mut() {
+        match frame.children.next() {
+            Some((key, child)) => {
+                let entry = frame.table + 4 * frame.index;
+                let offset = encoder.out.position() - frame.start;
+                frame.index += 1;
+                encoder.patch(entry, offset)?;
+                encoder.push_u32(key.len())?;
+                encoder.out.write(&key)?;
+                encoder.begin(child)?;
+            }
+            None => {
+                let start = frame.start;
+                encoder.stack.pop();
+                encoder.patch(start, encoder.out.position() - start)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+struct Encoder<'o, S, O> {
+    out: &'o mut O,
+    stack: Vec<Frame<S>>,
+}
+
+/// A node whose children are being written
+struct Frame<S> {
+    start: usize,
+    table: usize,
+    index: usize,
+    children: std::vec::IntoIter<(Vec<u8>, S)>,
+}
+
+impl<'s, S: Source<'s>, O: Output> Encoder<'_, S, O> {
+    /// Writes everything up to the child records, leaving the length and the
+    /// offsets to be patched once they are known
+    fn begin(&mut self, source: S) -> io::Result<()> {
+        let (value, mut children) = match source.parts() {
+            Parts::Encoded(bytes) => return self.out.write(bytes),
+            Parts::Node { value, children } => (value, children),
+        };
+
+        let start = self.out.position();
+        self.push_u32(0)?;
+        self.push_u32(value.len())?;
+        self.out.write(&value)?;
+
+        children.sort_by(|(left, _), (right, _)| left.cmp(right));
+        if children.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "two keys have the same encoding",
+            ));
+        }
+
+        self.push_u32(children.len())?;
+        let table = self.out.position();
+        self.out.write(&vec![0; 4 * children.len()])?;
+        self.stack.push(Frame {
+            start,
+            table,
+            index: 0,
+            children: children.into_iter(),
+        });
+        Ok(())
+    }
+
+    fn push_u32(&mut self, value: usize) -> io::Result<()> {
+        self.out.write(&to_u32(value)?)
+    }
+
+    fn patch(&mut self, at: usize, value: usize) -> io::Result<()> {
+        self.out.patch(at, to_u32(value)?)
+    }
+}
+
+fn to_u32(value: usize) -> io::Result<[u8; 4]> {
+    if value > u32::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the tree is too large for the binary format",
+        ));
+    }
+    Ok((value as u32).to_le_bytes())
+}
+
+/// Reads a `u32`, panicking if it is out of bounds
+#[inline]
+fn read_u32(bytes: &[u8], at: usize) -> usize {
+    let mut word = [0; 4];
+    word.copy_from_slice(&bytes[at..at + 4]);
+    u32::from_le_bytes(word) as usize
+}
+
+#[inline]
+fn try_read_u32(bytes: &[u8], at: usize) -> Result<usize, DecodeError> {
+    match at.checked_add(4) {
+        Some(end) if end <= bytes.len() => Ok(read_u32(bytes, at)),
+        _ => Err(DecodeError::new("unexpected end of node")),
+    }
+}
+
+#[inline]
+fn try_add(a: usize, b: usize) -> Result<usize, DecodeError> {
+    a.checked_add(b)
+        .ok_or_else(|| DecodeError::new("length out of range"))
+}
+
+#[inline]
+fn try_slice(
+    bytes: &[u8],
+    start: usize,
+    len: usize,
+) -> Result<&[u8], DecodeError> {
+    start
+        .checked_add(len)
+        .and_then(|end| bytes.get(start..end))
+        .ok_or_else(|| DecodeError::new("unexpected end of node"))
+}
+
+/// A tree read in place from its binary encoding
+pub struct ArchivedTree<'a, K, V> {
+    node: &'a [u8],
+    marker: PhantomData<fn() -> (K, V)>,
+}
+
+impl<'a, K, V> ArchivedTree<'a, K, V>
+where
+    K: Decode<'a>,
+    V: Decode<'a>,
+{
+    /// Checks the whole buffer, so that reading it afterwards can't fail
+    pub fn new(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        let root = Self::root(bytes)?;
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            if try_read_u32(node, 0)? != node.len() {
+                return Err(DecodeError::new("invalid node length"));
+            }
+            let value_len = try_read_u32(node, 4)?;
+            V::decode(try_slice(node, 8, value_len)?)?;
+            let count = try_read_u32(node, try_add(8, value_len)?)?;
+            let table = try_add(12, value_len)?;
+            let mut end = count
+                .checked_mul(4)
+                .and_then(|len| len.checked_add(table))
+                .filter(|end| *end <= node.len())
+                .ok_or_else(|| DecodeError::new("invalid child count"))?;
+
+            let mut previous: Option<&[u8]> = None;
+            for index in 0..count {
+                let offset = read_u32(node, table + 4 * index);
+                if offset != end {
+                    return Err(DecodeError::new("invalid child offset"));
+                }
+                let key_len = try_read_u32(node, offset)?;
+                let key = try_slice(node, try_add(offset, 4)?, key_len)?;
+                if previous.is_some_and(|previous| previous >= key) {
+                    return Err(DecodeError::new("unsorted children"));
+                }
+                K::decode(key)?;
+                previous = Some(key);
+
+                let start = try_add(offset + 4, key_len)?;
+                let child = try_slice(node, start, try_read_u32(node, start)?)?;
+                if child.len() < MIN_NODE_LEN {
+                    return Err(DecodeError::new("invalid node length"));
+                }
+                // the child was sliced out of the node, so this can't overflow
+                end = start + child.len();
+                stack.push(child);
+            }
+            if end != node.len() {
+                return Err(DecodeError::new("unexpected bytes after node"));
+            }
+        }
+
+        Ok(ArchivedTree {
+            node: root,
+            marker: PhantomData,
+        })
+    }
+
+    /// Skips checking the buffer past the length of the root, for bytes known
+    /// to be valid. Reading a malformed buffer panics.
+    pub fn trusted(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        let root = Self::root(bytes)?;
+        match root.get(..read_u32(root, 0)) {
+            Some(node) if node.len() >= MIN_NODE_LEN => Ok(ArchivedTree {
+                node,
+                marker: PhantomData,
+            }),
+            _ => Err(DecodeError::new("invalid root length")),
+        }
+    }
+
+    fn root(bytes: &'a [u8]) -> Result<&'a [u8], DecodeError> {
+        match bytes.strip_prefix(MAGIC) {
+            Some(root) if root.len() >= MIN_NODE_LEN => Ok(root),
+            _ => Err(DecodeError::new("missing magic bytes")),
+        }
+    }
+
+    #[inline]
+    pub fn value(&self) -> V {
+        V::decode(self.value_bytes()).expect("the value has been checked")
+    }
+
+    #[inline]
+    pub fn value_bytes(&self) -> &'a [u8] {
+        &self.node[8..8 + self.value_len()]
+    }
+
+    #[inline]
+    pub fn child_count(&self) -> usize {
+        read_u32(self.node, 8 + self.value_len())
+    }
+
+    #[inline]
+    pub fn is_childless(&self) -> bool {
+        self.child_count() == 0
+    }
+
+    /// Finds a child by binary searching for the encoded key
+    pub fn get_child<Q: Encode + ?Sized>(&self, key: &Q) -> Option<Self> {
+        let mut bytes = Vec::new();
+        key.encode(&mut bytes);
+
+        let (mut low, mut high) = (0, self.child_count());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let (key, child) = self.child(mid);
+            match key.cmp(&bytes[..]) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return Some(child),
+            }
+        }
+        None
+    }
+
+    /// Follows the keys down the tree
+    pub fn get_path<I>(&self, path: I) -> Option<Self>
+    where
+        I: IntoIterator,
+        I::Item: Encode,
+    {
+        path.into_iter()
+            .try_fold(*self, |node, key| node.get_child(&key))
+    }
+
+    #[inline]
+    pub fn children(&self) -> ArchivedChildren<'a, K, V> {
+        ArchivedChildren {
+            tree: *self,
+            range: 0..self.child_count(),
+        }
+    }
+
+    /// Decodes the whole tree
+    pub fn to_tree(&self) -> Tree<K, V>
+    where
+        K: Ord,
+    {
+        type Frame<'a, K, V> =
+            (Option<K>, Tree<K, V>, ArchivedChildren<'a, K, V>);
+
+        let mut stack: Vec<Frame<'a, K, V>> =
+            vec![(None, Tree::new(self.value()), self.children())];
+        loop {
+            let (_, _, children) = stack.last_mut().expect("the root is last");
+            if let Some((key, child)) = children.next() {
+                stack.push((
+                    Some(key),
+                    Tree::new(child.value()),
+                    child.children(),
+                ));
+                continue;
+            }
+
+            let (key, tree, _) = stack.pop().expect("the root is last");
+            match (key, stack.last_mut()) {
+                (Some(key), Some((_, parent, _))) => {
+                    parent.children.insert(key, tree);
+                }
+                _ => return tree,
+            }
+        }
+    }
+
+    #[inline]
+    fn value_len(&self) -> usize {
+        read_u32(self.node, 4)
+    }
+
+    /// The encoded key and the node of a child
+    fn child(&self, index: usize) -> (&'a [u8], Self) {
+        let table = 12 + self.value_len();
+        let offset = read_u32(self.node, table + 4 * index);
+        let key_len = read_u32(self.node, offset);
+        let start = offset + 4 + key_len;
+        let len = read_u32(self.node, start);
+        let child = ArchivedTree {
+            node: &self.node[start..start + len],
+            marker: PhantomData,
+        };
+        (&self.node[offset + 4..start], child)
+    }
+}
+
+impl<'a, K, V> ArchivedTree<'a, K, V> {
+    /// The bytes of this node and its descendants
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.node
+    }
+}
+
+impl<K, V> Clone for ArchivedTree<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> Copy for ArchivedTree<'_, K, V> {}
+
+impl<K, V> fmt::Debug for ArchivedTree<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchivedTree")
+            .field("len", &self.node.len())
+            .finish()
+    }
+}
+
+/// Iterates over the children of an [`ArchivedTree`] in the order of their
+/// encoded keys
+pub struct ArchivedChildren<'a, K, V> {
+    tree: ArchivedTree<'a, K, V>,
+    range: std::ops::Range<usize>,
+}
+
+impl<'a, K, V> Iterator for ArchivedChildren<'a, K, V>
+where
+    K: Decode<'a>,
+    V: Decode<'a>,
+{
+    type Item = (K, ArchivedTree<'a, K, V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, child) = self.tree.child(self.range.next()?);
+        Some((K::decode(key).expect("the key has been checked"), child))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for ArchivedChildren<'a, K, V>
+where
+    K: Decode<'a>,
+    V: Decode<'a>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (key, child) = self.tree.child(self.range.next_back()?);
+        Some((K::decode(key).expect("the key has been checked"), child))
+    }
+}
+
+impl<'a, K: Decode<'a>, V: Decode<'a>> ExactSizeIterator
+    for ArchivedChildren<'a, K, V>
+{
+}
+
+impl<'a, K: Decode<'a>, V: Decode<'a>> FusedIterator
+    for ArchivedChildren<'a, K, V>
+{
+}
+
+impl<K, V> Clone for ArchivedChildren<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        ArchivedChildren {
+            tree: self.tree,
+            range: self.range.clone(),
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for ArchivedChildren<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchivedChildren")
+            .field("remaining", &self.range.len())
+            .finish()
+    }
+}
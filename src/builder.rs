@@ -0,0 +1,255 @@
+use crate::Tree;
+use alloc::{
+    borrow::ToOwned, collections::btree_map, string::String, vec, vec::Vec,
+};
+use core::{fmt, iter::FromIterator};
+
+/// Builds a tree from the paths to its nodes, filling in the nodes along the
+/// paths that aren't given a value
+///
+/// ```
+/// use tagged_tree::TreeBuilder;
+///
+/// let mut builder = TreeBuilder::with_default(|| -1);
+/// builder.insert(vec!["a", "b"], 2).unwrap();
+/// builder.insert(vec!["c"], 3).unwrap();
+/// assert!(builder.insert(vec!["a", "b"], 4).is_err());
+///
+/// let tree = builder.build();
+/// assert_eq!(*tree.value(), -1);
+/// assert_eq!(*tree[&"a"].value(), -1);
+/// assert_eq!(*tree[&"a"][&"b"].value(), 2);
+/// ```
+pub struct TreeBuilder<K: Ord, V, F = fn() -> V> {
+    tree: Tree<K, Option<V>>,
+    default: F,
+}
+
+impl<K: Ord, V: Default> TreeBuilder<K, V> {
+    /// Fills in the missing values with `V::default()`
+    #[inline]
+    pub fn new() -> Self {
+        TreeBuilder::with_default(V::default)
+    }
+}
+
+impl<K: Ord, V: Default> Default for TreeBuilder<K, V> {
+    #[inline]
+    fn default() -> Self {
+        TreeBuilder::new()
+    }
+}
+
+impl<K: Ord, V, F: FnMut() -> V> TreeBuilder<K, V, F> {
+    /// Fills in the missing values by calling `default`
+    #[inline]
+    pub fn with_default(default: F) -> Self {
+        TreeBuilder {
+            tree: Tree::new(None),
+            default,
+        }
+    }
+
+    /// Sets the value at the path, the empty path being the root. Fails if
+    /// the path already has a value.
+    pub fn insert<P>(
+        &mut self,
+        path: P,
+        value: V,
+    ) -> Result<&mut Self, DuplicatePathError<K>>
+    where
+        P: IntoIterator<Item = K>,
+    {
+        self.set(path, value, false).map(|_| self)
+    }
+
+    /// Sets the value at the path, returning the one it replaces
+    #[inline]
+    pub fn replace<P>(&mut self, path: P, value: V) -> Option<V>
+    where
+        P: IntoIterator<Item = K>,
+    {
+        match self.set(path, value, true) {
+            Ok(old) => old,
+            Err(_) => {
+                unreachable!("replacing never fails because of duplicates")
+            }
+        }
+    }
+
+    /// Fills in the missing values and returns the tree
+    pub fn build(self) -> Tree<K, V> {
+        let mut default = self.default;
+        map_values(self.tree, |value| value.unwrap_or_else(&mut default))
+    }
+
+    fn set<P>(
+        &mut self,
+        path: P,
+        value: V,
+        replace: bool,
+    ) -> Result<Option<V>, DuplicatePathError<K>>
+    where
+        P: IntoIterator<Item = K>,
+    {
+        let path: Vec<K> = path.into_iter().collect();
+
+        // follow the existing nodes by reference, so the keys are still
+        // around if the path turns out to be a duplicate
+        let mut existing = 0;
+        let mut node = &self.tree;
+        while let Some(child) =
+            path.get(existing).and_then(|k| node.get_child(k))
+        {
+            node = child;
+            existing += 1;
+        }
+        if existing == path.len() && node.value.is_some() && !replace {
+            return Err(DuplicatePathError { path });
+        }
+
+        let mut path = path.into_iter();
+        let mut node = path
+            .by_ref()
+            .take(existing)
+            .try_fold(&mut self.tree, |node, key| node.get_child_mut(&key))
+            .expect("the nodes have just been found");
+        for key in path {
+            node = node.entry(key).or_insert(None);
+        }
+        Ok(node.value.replace(value))
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug, F> fmt::Debug
+    for TreeBuilder<K, V, F>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TreeBuilder")
+            .field("tree", &self.tree)
+            .finish()
+    }
+}
+
+/// The error returned when a path is given a value twice
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DuplicatePathError<K> {
+    path: Vec<K>,
+}
+
+impl<K> DuplicatePathError<K> {
+    #[inline]
+    pub fn path(&self) -> &[K] {
+        &self.path
+    }
+
+    #[inline]
+    pub fn into_path(self) -> Vec<K> {
+        self.path
+    }
+}
+
+impl<K: fmt::Debug> fmt::Display for DuplicatePathError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "duplicate path {:?}", self.path)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: fmt::Debug> std::error::Error for DuplicatePathError<K> {}
+
+/// Collects paths and their values, filling in the missing values with
+/// `V::default()`. When a path is given more than once, the last value wins.
+impl<K, V, P> FromIterator<(P, V)> for Tree<K, V>
+where
+    K: Ord,
+    V: Default,
+    P: IntoIterator<Item = K>,
+{
+    fn from_iter<I: IntoIterator<Item = (P, V)>>(iter: I) -> Self {
+        let mut builder = TreeBuilder::new();
+        for (path, value) in iter {
+            builder.replace(path, value);
+        }
+        builder.build()
+    }
+}
+
+impl<K: Ord, V: Default> Tree<K, V> {
+    /// Collects paths and their values like `collect`, but fails on paths
+    /// given more than once
+    pub fn try_from_paths<I, P>(iter: I) -> Result<Self, DuplicatePathError<K>>
+    where
+        I: IntoIterator<Item = (P, V)>,
+        P: IntoIterator<Item = K>,
+    {
+        let mut builder = TreeBuilder::new();
+        for (path, value) in iter {
+            builder.insert(path, value)?;
+        }
+        Ok(builder.build())
+    }
+}
+
+impl<V: Default> Tree<String, V> {
+    /// Builds a tree with default values from paths like `a/b/c`, split on
+    /// the separator. Empty segments are skipped, so the empty string is the
+    /// root.
+    ///
+    /// ```
+    /// use tagged_tree::Tree;
+    ///
+    /// let tree: Tree<String, ()> =
+    ///     Tree::from_delimited_strings(&["a/b/c", "a/d"], "/").unwrap();
+    /// assert!(tree["a"]["b"].contains_key("c"));
+    /// assert!(tree["a"].contains_key("d"));
+    /// ```
+    pub fn from_delimited_strings<I, S>(
+        paths: I,
+        separator: &str,
+    ) -> Result<Self, DuplicatePathError<String>>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut builder = TreeBuilder::new();
+        for path in paths {
+            let keys = path
+                .as_ref()
+                .split(separator)
+                .filter(|key| !key.is_empty())
+                .map(str::to_owned);
+            builder.insert(keys, V::default())?;
+        }
+        Ok(builder.build())
+    }
+}
+
+/// Converts every value of the tree, without recursing
+fn map_values<K: Ord, V, W, F>(tree: Tree<K, V>, mut f: F) -> Tree<K, W>
+where
+    F: FnMut(V) -> W,
+{
+    type Frame<K, V, W> =
+        (Option<K>, Tree<K, W>, btree_map::IntoIter<K, Tree<K, V>>);
+
+    let (value, children) = tree.into_parts();
+    let mut stack: Vec<Frame<K, V, W>> =
+        vec![(None, Tree::new(f(value)), children.into_iter())];
+    loop {
+        let (_, _, children) = stack.last_mut().expect("the root is last");
+        if let Some((key, child)) = children.next() {
+            let (value, children) = child.into_parts();
+            stack.push((Some(key), Tree::new(f(value)), children.into_iter()));
+            continue;
+        }
+
+        let (key, tree, _) = stack.pop().expect("the root is last");
+        match (key, stack.last_mut()) {
+            (Some(key), Some((_, parent, _))) => {
+                parent.children.insert(key, tree);
+            }
+            _ => return tree,
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Serializes a tree as nested maps, suited for hand written configuration
+//! files.
+//!
+//! Each node is a map from its children's keys to the children, with its own
+//! value stored under the reserved `$value` key. Trees of `()` can be written
+//! as plain nested maps of keys instead, leaving out the `$value` entries, with
+//! [`keys`]. Keys are written with their `Display` implementation and read
+//! back with `FromStr`.
+//!
+//! Use it with `#[serde(with = "tagged_tree::compact")]`, or
+//! `#[serde(with = "tagged_tree::compact::keys")]`.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use tagged_tree::Tree;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "tagged_tree::compact")]
+//!     limits: Tree<String, u32>,
+//!     #[serde(with = "tagged_tree::compact::keys")]
+//!     layout: Tree<String, ()>,
+//! }
+//!
+//! let config: Config = serde_json::from_str(r#"{
+//!     "limits": { "$value": 10, "cpu": { "$value": 2 } },
+//!     "layout": { "src": { "bin": {} }, "tests": {} }
+//! }"#).unwrap();
+//! assert_eq!(*config.limits[&"cpu".to_owned()].value(), 2);
+//! assert!(config.layout[&"src".to_owned()].contains_key("bin"));
+//! ```
+
+use crate::Tree;
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, marker::PhantomData, str::FromStr};
+use serde::{
+    de::{
+        self, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess,
+        Visitor,
+    },
+    ser::{self, Serialize, SerializeMap, Serializer},
+    Deserialize,
+};
+
+pub mod keys;
+
+/// The key holding the value of a node
+pub const VALUE_KEY: &str = "$value";
+
+pub fn serialize<K, V, S>(
+    tree: &Tree<K, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    K: Ord + fmt::Display,
+    V: Serialize,
+    S: Serializer,
+{
+    Node { tree, values: true }.serialize(serializer)
+}
+
+pub fn deserialize<'de, K, V, D>(
+    deserializer: D,
+) -> Result<Tree<K, V>, D::Error>
+where
+    K: Ord + FromStr,
+    K::Err: fmt::Display,
+    V: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    NodeSeed(PhantomData).deserialize(deserializer)
+}
+
+struct Node<'a, K: Ord, V> {
+    tree: &'a Tree<K, V>,
+    /// Whether the values are written under [`VALUE_KEY`]
+    values: bool,
+}
+
+impl<K: Ord + fmt::Display, V: Serialize> Serialize for Node<'_, K, V> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let tree = self.tree;
+        let len = tree.children().len() + self.values as usize;
+        let mut map = serializer.serialize_map(Some(len))?;
+        if self.values {
+            map.serialize_entry(VALUE_KEY, tree.value())?;
+        }
+        for (key, child) in tree.iter_single() {
+            let key = key.to_string();
+            if key == VALUE_KEY {
+                return Err(ser::Error::custom(format_args!(
+                    "the key `{}` is reserved",
+                    VALUE_KEY
+                )));
+            }
+            let child = Node {
+                tree: child,
+                values: self.values,
+            };
+            map.serialize_entry(&key, &child)?;
+        }
+        map.end()
+    }
+}
+
+struct NodeSeed<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K, V> DeserializeSeed<'de> for NodeSeed<K, V>
+where
+    K: Ord + FromStr,
+    K::Err: fmt::Display,
+    V: Deserialize<'de>,
+{
+    type Value = Tree<K, V>;
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, K, V> Visitor<'de> for NodeSeed<K, V>
+where
+    K: Ord + FromStr,
+    K::Err: fmt::Display,
+    V: Deserialize<'de>,
+{
+    type Value = Tree<K, V>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of keys to nodes")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> Result<Self::Value, A::Error> {
+        let mut value = None;
+        let mut children = Vec::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if key == VALUE_KEY {
+                if value.is_some() {
+                    return Err(de::Error::duplicate_field(VALUE_KEY));
+                }
+                value = Some(map.next_value()?);
+                continue;
+            }
+
+            let parsed: K = key.parse().map_err(|err| {
+                de::Error::custom(format_args!(
+                    "invalid key `{}`: {}",
+                    key, err
+                ))
+            })?;
+            let child = map.next_value_seed(NodeSeed(PhantomData))?;
+            children.push((key, parsed, child));
+        }
+
+        let value = match value {
+            Some(value) => value,
+            None => V::deserialize(().into_deserializer()).map_err(
+                |_: de::value::Error| de::Error::missing_field(VALUE_KEY),
+            )?,
+        };
+        let mut tree = Tree::new(value);
+        for (raw, key, child) in children {
+            if tree.contains_key(&key) {
+                return Err(de::Error::custom(format_args!(
+                    "duplicate key `{}`",
+                    raw
+                )));
+            }
+            tree.entry(key).or_insert_tree(child);
+        }
+        Ok(tree)
+    }
+}
@@ -0,0 +1,34 @@
+//! Serializes a tree of `()` as plain nested maps of keys, leaving out the
+//! `$value` entries. Reading accepts them, so the output of
+//! [`compact`](super) can be read back too.
+//!
+//! Use it with `#[serde(with = "tagged_tree::compact::keys")]`.
+
+use super::{Node, NodeSeed};
+use crate::Tree;
+use core::{fmt, marker::PhantomData, str::FromStr};
+use serde::{de::DeserializeSeed, Deserializer, Serialize, Serializer};
+
+pub fn serialize<K, S>(
+    tree: &Tree<K, ()>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    K: Ord + fmt::Display,
+    S: Serializer,
+{
+    Node {
+        tree,
+        values: false,
+    }
+    .serialize(serializer)
+}
+
+pub fn deserialize<'de, K, D>(deserializer: D) -> Result<Tree<K, ()>, D::Error>
+where
+    K: Ord + FromStr,
+    K::Err: fmt::Display,
+    D: Deserializer<'de>,
+{
+    NodeSeed(PhantomData).deserialize(deserializer)
+}
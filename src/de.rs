@@ -0,0 +1,444 @@
+//! Deserializes trees in the shape produced by their `Serialize`
+//! implementation, with a limit on how deep the input may nest.
+//!
+//! Serde only hands nested data to a visitor through nested calls, so each
+//! level of the input still takes a few stack frames in the format's
+//! deserializer, and formats like `serde_json` enforce their own recursion
+//! limit. The nodes themselves are collected on an explicit stack, and
+//! inputs nesting deeper than [`DEFAULT_MAX_DEPTH`] are rejected before they
+//! can exhaust the stack, including by the `Deserialize` implementation of
+//! [`Tree`]. Use [`TreeSeed::max_depth`] to change the limit, or lift it with
+//! `usize::MAX` for formats that limit their own recursion.
+//!
+//! Nodes can be streamed to a [`NodeVisitor`] with [`VisitorSeed`] instead
+//! of being collected into a [`Tree`], so huge inputs can be processed without
+//! holding all of them in memory.
+//!
+//! ```
+//! use serde::de::DeserializeSeed;
+//! use tagged_tree::{de::TreeSeed, Tree};
+//!
+//! let json = r#"{"value":0,"children":{"a":{"value":1,"children":{}}}}"#;
+//! let tree: Tree<String, u32> = TreeSeed::new()
+//!     .max_depth(1)
+//!     .deserialize(&mut serde_json::Deserializer::from_str(json))
+//!     .unwrap();
+//! assert_eq!(*tree[&"a".to_owned()].value(), 1);
+//! ```
+
+use crate::Tree;
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{fmt, marker::PhantomData};
+use serde::de::{
+    self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess,
+    Visitor,
+};
+use serde::Deserialize;
+
+/// The deepest a tree may nest when deserialized with no other limit set,
+/// the root being at depth zero
+pub const DEFAULT_MAX_DEPTH: usize = 512;
+
+const FIELDS: &[&str] = &["value", "children"];
+
+/// Receives the nodes of a tree as they are deserialized.
+///
+/// Every node is announced with [`enter`](NodeVisitor::enter), followed by its
+/// value and its children in the order they appear in the input, and closed
+/// with [`leave`](NodeVisitor::leave).
+pub trait NodeVisitor<K, V> {
+    type Error: fmt::Display;
+
+    /// Starts a node, the key is `None` for the root
+    fn enter(
+        &mut self,
+        key: Option<K>,
+        depth: usize,
+    ) -> Result<(), Self::Error>;
+
+    /// Receives the value of the current node
+    fn value(&mut self, value: V) -> Result<(), Self::Error>;
+
+    /// Ends the current node
+    fn leave(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Deserializes a [`Tree`] with a configurable maximum depth
+pub struct TreeSeed<K, V> {
+    max_depth: usize,
+    marker: PhantomData<(K, V)>,
+}
+
+impl<K, V> TreeSeed<K, V> {
+    #[inline]
+    pub fn new() -> Self {
+        TreeSeed {
+            max_depth: DEFAULT_MAX_DEPTH,
+            marker: PhantomData,
+        }
+    }
+
+    /// Rejects inputs nested deeper than `depth`, the root being at depth
+    /// zero
+    #[inline]
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+}
+
+impl<K, V> Default for TreeSeed<K, V> {
+    #[inline]
+    fn default() -> Self {
+        TreeSeed::new()
+    }
+}
+
+impl<K, V> fmt::Debug for TreeSeed<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TreeSeed")
+            .field("max_depth", &self.max_depth)
+            .finish()
+    }
+}
+
+impl<'de, K, V> DeserializeSeed<'de> for TreeSeed<K, V>
+where
+    K: Ord + Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = Tree<K, V>;
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        let mut builder = TreeBuilder {
+            stack: Vec::new(),
+            tree: None,
+        };
+        VisitorSeed::new(&mut builder)
+            .max_depth(self.max_depth)
+            .deserialize(deserializer)?;
+        Ok(builder.tree.expect("the root has been left"))
+    }
+}
+
+impl<'de, K, V> Deserialize<'de> for Tree<K, V>
+where
+    K: Ord + Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        TreeSeed::new().deserialize(deserializer)
+    }
+}
+
+/// Streams the nodes of a tree to a [`NodeVisitor`]
+pub struct VisitorSeed<'v, K, V, N> {
+    visitor: &'v mut N,
+    max_depth: usize,
+    marker: PhantomData<(K, V)>,
+}
+
+impl<'v, K, V, N: NodeVisitor<K, V>> VisitorSeed<'v, K, V, N> {
+    #[inline]
+    pub fn new(visitor: &'v mut N) -> Self {
+        VisitorSeed {
+            visitor,
+            max_depth: DEFAULT_MAX_DEPTH,
+            marker: PhantomData,
+        }
+    }
+
+    /// Rejects inputs nested deeper than `depth`, the root being at depth
+    /// zero
+    #[inline]
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+}
+
+impl<'de, K, V, N> DeserializeSeed<'de> for VisitorSeed<'_, K, V, N>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+    N: NodeVisitor<K, V>,
+{
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<(), D::Error> {
+        NodeSeed {
+            visitor: self.visitor,
+            max_depth: self.max_depth,
+            key: None,
+            depth: 0,
+            marker: PhantomData,
+        }
+        .deserialize(deserializer)
+    }
+}
+
+/// Collects the streamed nodes into a tree, keeping the unfinished nodes on a
+/// stack
+struct TreeBuilder<K: Ord, V> {
+    stack: Vec<Partial<K, V>>,
+    tree: Option<Tree<K, V>>,
+}
+
+struct Partial<K: Ord, V> {
+    key: Option<K>,
+    value: Option<V>,
+    children: BTreeMap<K, Tree<K, V>>,
+}
+
+impl<K: Ord, V> NodeVisitor<K, V> for TreeBuilder<K, V> {
+    type Error = core::convert::Infallible;
+
+    fn enter(&mut self, key: Option<K>, _: usize) -> Result<(), Self::Error> {
+        self.stack.push(Partial {
+            key,
+            value: None,
+            children: BTreeMap::new(),
+        });
+        Ok(())
+    }
+
+    fn value(&mut self, value: V) -> Result<(), Self::Error> {
+        let node = self.stack.last_mut().expect("a node has been entered");
+        node.value = Some(value);
+        Ok(())
+    }
+
+    fn leave(&mut self) -> Result<(), Self::Error> {
+        let node = self.stack.pop().expect("a node has been entered");
+        let tree = Tree {
+            value: node.value.expect("nodes are left after their value"),
+            children: node.children,
+        };
+        match (node.key, self.stack.last_mut()) {
+            (Some(key), Some(parent)) => {
+                parent.children.insert(key, tree);
+            }
+            _ => self.tree = Some(tree),
+        }
+        Ok(())
+    }
+}
+
+struct NodeSeed<'v, K, V, N> {
+    visitor: &'v mut N,
+    max_depth: usize,
+    key: Option<K>,
+    depth: usize,
+    marker: PhantomData<V>,
+}
+
+impl<'v, K, V, N: NodeVisitor<K, V>> NodeSeed<'v, K, V, N> {
+    fn enter<E: de::Error>(&mut self) -> Result<(), E> {
+        if self.depth > self.max_depth {
+            return Err(E::custom(format_args!(
+                "the tree is nested deeper than {} levels",
+                self.max_depth
+            )));
+        }
+        self.visitor
+            .enter(self.key.take(), self.depth)
+            .map_err(E::custom)
+    }
+
+    fn children(&mut self) -> ChildrenSeed<'_, K, V, N> {
+        ChildrenSeed {
+            visitor: self.visitor,
+            max_depth: self.max_depth,
+            depth: self.depth + 1,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, K, V, N> DeserializeSeed<'de> for NodeSeed<'_, K, V, N>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+    N: NodeVisitor<K, V>,
+{
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<(), D::Error> {
+        deserializer.deserialize_struct("Tree", FIELDS, self)
+    }
+}
+
+impl<'de, K, V, N> Visitor<'de> for NodeSeed<'_, K, V, N>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+    N: NodeVisitor<K, V>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("struct Tree")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        mut self,
+        mut seq: A,
+    ) -> Result<(), A::Error> {
+        self.enter()?;
+        let value: V = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        self.visitor.value(value).map_err(de::Error::custom)?;
+        if seq.next_element_seed(self.children())?.is_none() {
+            return Err(de::Error::invalid_length(1, &self));
+        }
+        self.visitor.leave().map_err(de::Error::custom)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        mut self,
+        mut map: A,
+    ) -> Result<(), A::Error> {
+        self.enter()?;
+        let mut has_value = false;
+        let mut has_children = false;
+        while let Some(field) = map.next_key()? {
+            match field {
+                Field::Value => {
+                    if has_value {
+                        return Err(de::Error::duplicate_field("value"));
+                    }
+                    has_value = true;
+                    let value: V = map.next_value()?;
+                    self.visitor.value(value).map_err(de::Error::custom)?;
+                }
+                Field::Children => {
+                    if has_children {
+                        return Err(de::Error::duplicate_field("children"));
+                    }
+                    has_children = true;
+                    map.next_value_seed(self.children())?;
+                }
+                Field::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        if !has_value {
+            return Err(de::Error::missing_field("value"));
+        }
+        if !has_children {
+            return Err(de::Error::missing_field("children"));
+        }
+        self.visitor.leave().map_err(de::Error::custom)
+    }
+}
+
+struct ChildrenSeed<'v, K, V, N> {
+    visitor: &'v mut N,
+    max_depth: usize,
+    depth: usize,
+    marker: PhantomData<(K, V)>,
+}
+
+impl<'de, K, V, N> DeserializeSeed<'de> for ChildrenSeed<'_, K, V, N>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+    N: NodeVisitor<K, V>,
+{
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, K, V, N> Visitor<'de> for ChildrenSeed<'_, K, V, N>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+    N: NodeVisitor<K, V>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of children")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key()? {
+            map.next_value_seed(NodeSeed {
+                visitor: &mut *self.visitor,
+                max_depth: self.max_depth,
+                key: Some(key),
+                depth: self.depth,
+                marker: PhantomData,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+enum Field {
+    Value,
+    Children,
+    Other,
+}
+
+impl<'de> Deserialize<'de> for Field {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(FieldVisitor)
+    }
+}
+
+struct FieldVisitor;
+
+impl Visitor<'_> for FieldVisitor {
+    type Value = Field;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a field identifier")
+    }
+
+    fn visit_u64<E: de::Error>(self, index: u64) -> Result<Field, E> {
+        Ok(match index {
+            0 => Field::Value,
+            1 => Field::Children,
+            _ => Field::Other,
+        })
+    }
+
+    fn visit_str<E: de::Error>(self, name: &str) -> Result<Field, E> {
+        Ok(match name {
+            "value" => Field::Value,
+            "children" => Field::Children,
+            _ => Field::Other,
+        })
+    }
+
+    fn visit_bytes<E: de::Error>(self, name: &[u8]) -> Result<Field, E> {
+        Ok(match name {
+            b"value" => Field::Value,
+            b"children" => Field::Children,
+            _ => Field::Other,
+        })
+    }
+}
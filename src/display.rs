@@ -0,0 +1,214 @@
+//! Renders trees in the style of the `tree` command.
+//!
+//! ```text
+//! 0
+//! ├── a: 1
+//! │   └── b: 2
+//! └── c: 3
+//! ```
+
+use crate::Tree;
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{self, Display, Formatter};
+
+/// The characters used to draw the branches of the tree
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Style {
+    /// Box drawing characters: `├──`, `└──` and `│`
+    Unicode,
+    /// Plain ASCII characters: `|--`, `` `-- `` and `|`
+    Ascii,
+}
+
+impl Default for Style {
+    #[inline]
+    fn default() -> Self {
+        Style::Unicode
+    }
+}
+
+impl Style {
+    fn branch(self, last: bool) -> &'static str {
+        match (self, last) {
+            (Style::Unicode, false) => "├── ",
+            (Style::Unicode, true) => "└── ",
+            (Style::Ascii, false) => "|-- ",
+            (Style::Ascii, true) => "`-- ",
+        }
+    }
+
+    fn indent(self, last: bool) -> &'static str {
+        match (self, last) {
+            (_, true) => "    ",
+            (Style::Unicode, false) => "│   ",
+            (Style::Ascii, false) => "|   ",
+        }
+    }
+}
+
+impl<K: Ord, V> Tree<K, V> {
+    /// Renders the tree using the closure to format each node. The key is
+    /// `None` for the root.
+    #[inline]
+    pub fn display_with<F, D>(&self, label: F) -> DisplayTree<'_, K, V, F>
+    where
+        F: Fn(Option<&K>, &V) -> D,
+        D: Display,
+    {
+        DisplayTree {
+            tree: self,
+            label,
+            style: Style::default(),
+            max_depth: None,
+            max_children: None,
+        }
+    }
+}
+
+/// Renders a tree, created by [`Tree::display_with`]
+#[derive(Clone)]
+pub struct DisplayTree<'a, K: Ord, V, F> {
+    tree: &'a Tree<K, V>,
+    label: F,
+    style: Style,
+    max_depth: Option<usize>,
+    max_children: Option<usize>,
+}
+
+impl<K: Ord, V, F> DisplayTree<'_, K, V, F> {
+    #[inline]
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    #[inline]
+    pub fn ascii(self) -> Self {
+        self.style(Style::Ascii)
+    }
+
+    /// Stops rendering below the given depth, the root being at depth zero
+    #[inline]
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Renders at most `count` children of each node, followed by a line
+    /// saying how many were left out
+    #[inline]
+    pub fn max_children(mut self, count: usize) -> Self {
+        self.max_children = Some(count);
+        self
+    }
+}
+
+enum Line<'a, K: Ord, V> {
+    Node {
+        key: &'a K,
+        tree: &'a Tree<K, V>,
+        depth: usize,
+        last: bool,
+        prefix: String,
+    },
+    More {
+        count: usize,
+        prefix: String,
+    },
+}
+
+impl<K: Ord, V, F, D> Display for DisplayTree<'_, K, V, F>
+where
+    F: Fn(Option<&K>, &V) -> D,
+    D: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", (self.label)(None, self.tree.value()))?;
+
+        let mut stack = Vec::new();
+        self.push_children(&mut stack, self.tree, 0, "");
+        while let Some(line) = stack.pop() {
+            match line {
+                Line::Node {
+                    key,
+                    tree,
+                    depth,
+                    last,
+                    prefix,
+                } => {
+                    writeln!(
+                        f,
+                        "{}{}{}",
+                        prefix,
+                        self.style.branch(last),
+                        (self.label)(Some(key), tree.value())
+                    )?;
+                    let prefix = prefix + self.style.indent(last);
+                    self.push_children(&mut stack, tree, depth, &prefix);
+                }
+                Line::More { count, prefix } => {
+                    writeln!(
+                        f,
+                        "{}{}... {} more",
+                        prefix,
+                        self.style.branch(true),
+                        count
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, K: Ord, V, F> DisplayTree<'a, K, V, F> {
+    /// Pushes the lines for the children of a node in reverse order, so they
+    /// are popped in order
+    fn push_children(
+        &self,
+        stack: &mut Vec<Line<'a, K, V>>,
+        tree: &'a Tree<K, V>,
+        depth: usize,
+        prefix: &str,
+    ) {
+        if self.max_depth.is_some_and(|max| depth >= max) {
+            return;
+        }
+
+        let total = tree.children().len();
+        let shown = self.max_children.map_or(total, |max| total.min(max));
+        if shown < total {
+            stack.push(Line::More {
+                count: total - shown,
+                prefix: prefix.to_owned(),
+            });
+        }
+        for (index, (key, child)) in
+            tree.iter_single().take(shown).enumerate().rev()
+        {
+            stack.push(Line::Node {
+                key,
+                tree: child,
+                depth: depth + 1,
+                last: index + 1 == total,
+                prefix: prefix.to_owned(),
+            });
+        }
+    }
+}
+
+impl<K: Ord + Display, V: Display> Display for Tree<K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.display_with(|key, value| match key {
+            Some(key) => format!("{}: {}", key, value),
+            None => value.to_string(),
+        })
+        .fmt(f)
+    }
+}
@@ -0,0 +1,228 @@
+//! Exports trees as [Graphviz](https://graphviz.org) DOT graphs.
+//!
+//! Values are used as node labels and keys as edge labels.
+
+use crate::Tree;
+use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{self, Display, Write};
+
+type NodeAttributes<'a, K, V> =
+    Box<dyn Fn(&[&K], &V) -> Vec<(String, String)> + 'a>;
+
+/// Controls how a tree is exported by [`Tree::to_dot`]
+pub struct DotOptions<'a, K, V> {
+    name: String,
+    cluster_by_depth: bool,
+    max_depth: Option<usize>,
+    max_nodes: Option<usize>,
+    node_attributes: Option<NodeAttributes<'a, K, V>>,
+}
+
+impl<'a, K, V> DotOptions<'a, K, V> {
+    #[inline]
+    pub fn new() -> Self {
+        DotOptions {
+            name: "tree".to_owned(),
+            cluster_by_depth: false,
+            max_depth: None,
+            max_nodes: None,
+            node_attributes: None,
+        }
+    }
+
+    /// The name of the graph, defaults to `tree`
+    #[inline]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Groups the nodes at each depth in their own cluster
+    #[inline]
+    pub fn cluster_by_depth(mut self, cluster: bool) -> Self {
+        self.cluster_by_depth = cluster;
+        self
+    }
+
+    /// Leaves out the nodes below the given depth, the root being at depth
+    /// zero
+    #[inline]
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Leaves out any nodes after the first `count`, counting the root, so
+    /// zero leaves out every node
+    #[inline]
+    pub fn max_nodes(mut self, count: usize) -> Self {
+        self.max_nodes = Some(count);
+        self
+    }
+
+    /// Adds attributes to each node, given the keys leading to it and its
+    /// value. A `label` attribute replaces the default label. Names that
+    /// aren't plain identifiers are quoted.
+    #[inline]
+    pub fn node_attributes<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&[&K], &V) -> Vec<(String, String)> + 'a,
+    {
+        self.node_attributes = Some(Box::new(f));
+        self
+    }
+}
+
+impl<K, V> Default for DotOptions<'_, K, V> {
+    #[inline]
+    fn default() -> Self {
+        DotOptions::new()
+    }
+}
+
+impl<K, V> fmt::Debug for DotOptions<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DotOptions")
+            .field("name", &self.name)
+            .field("cluster_by_depth", &self.cluster_by_depth)
+            .field("max_depth", &self.max_depth)
+            .field("max_nodes", &self.max_nodes)
+            .field("node_attributes", &self.node_attributes.is_some())
+            .finish()
+    }
+}
+
+impl<K: Ord + Display, V: Display> Tree<K, V> {
+    /// Renders the tree as a DOT graph
+    pub fn to_dot(&self, options: &DotOptions<'_, K, V>) -> String {
+        let mut dot = String::new();
+        self.write_dot(&mut dot, options)
+            .expect("writing to a string can't fail");
+        dot
+    }
+
+    /// Writes the tree as a DOT graph
+    pub fn write_dot<W: Write>(
+        &self,
+        out: &mut W,
+        options: &DotOptions<'_, K, V>,
+    ) -> fmt::Result {
+        // node statements grouped by depth, so they can be clustered
+        let mut nodes: Vec<Vec<String>> = Vec::new();
+        let mut edges = Vec::new();
+        let mut truncated = false;
+
+        // the ids are handed out in depth first order, the root's being 0,
+        // so the parent of each node is the last one seen one level up
+        let mut ancestors: Vec<usize> = Vec::new();
+        let mut paths = self.iter_paths();
+        let mut root = Some((Vec::new(), self));
+        let mut id = 0;
+        while let Some((path, tree)) = root.take().or_else(|| paths.next()) {
+            if options.max_nodes.is_some_and(|max| id >= max) {
+                truncated = true;
+                break;
+            }
+
+            let depth = path.len();
+            if nodes.len() <= depth {
+                nodes.push(Vec::new());
+            }
+            nodes[depth].push(node(options, id, &path, tree));
+            ancestors.truncate(depth);
+            if let Some(parent) = ancestors.last() {
+                edges.push(format!(
+                    "n{} -> n{} [label=\"{}\"];",
+                    parent,
+                    id,
+                    escape(path[depth - 1])
+                ));
+            }
+            ancestors.push(id);
+            id += 1;
+
+            // the children are never visited past the limit
+            if !tree.is_childless()
+                && options.max_depth.is_some_and(|max| depth >= max)
+            {
+                truncated = true;
+                paths.skip_children(tree);
+            }
+        }
+
+        writeln!(out, "digraph \"{}\" {{", escape(&options.name))?;
+        for (depth, nodes) in nodes.iter().enumerate() {
+            if options.cluster_by_depth {
+                writeln!(out, "    subgraph cluster_depth_{} {{", depth)?;
+                writeln!(out, "        label=\"depth {}\";", depth)?;
+                for node in nodes {
+                    writeln!(out, "        {}", node)?;
+                }
+                writeln!(out, "    }}")?;
+            } else {
+                for node in nodes {
+                    writeln!(out, "    {}", node)?;
+                }
+            }
+        }
+        for edge in &edges {
+            writeln!(out, "    {}", edge)?;
+        }
+        if truncated {
+            writeln!(out, "    truncated [label=\"...\", shape=plaintext];")?;
+        }
+        writeln!(out, "}}")
+    }
+}
+
+fn node<K: Ord, V: Display>(
+    options: &DotOptions<'_, K, V>,
+    id: usize,
+    path: &[&K],
+    tree: &Tree<K, V>,
+) -> String {
+    let mut attributes = options
+        .node_attributes
+        .as_ref()
+        .map(|f| f(path, tree.value()))
+        .unwrap_or_default();
+    if !attributes.iter().any(|(name, _)| name == "label") {
+        attributes.insert(0, ("label".to_owned(), tree.value().to_string()));
+    }
+
+    let attributes: Vec<String> = attributes
+        .iter()
+        .map(|(name, value)| {
+            format!("{}=\"{}\"", attribute_name(name), escape(value))
+        })
+        .collect();
+    format!("n{} [{}];", id, attributes.join(", "))
+}
+
+/// Quotes attribute names that aren't plain DOT identifiers
+fn attribute_name(name: &str) -> String {
+    let mut chars = name.chars();
+    let plain = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if plain {
+        name.to_owned()
+    } else {
+        format!("\"{}\"", escape(name))
+    }
+}
+
+fn escape(value: impl Display) -> String {
+    value
+        .to_string()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,176 @@
+//! Serializes a tree as a flat map from paths to values.
+//!
+//! Paths are written like [JSON pointers](https://tools.ietf.org/html/rfc6901):
+//! the root is the empty string and every key is prefixed with a `/`, with `~`
+//! and `/` in keys escaped as `~0` and `~1`. Keys are written with their
+//! `Display` implementation and read back with `FromStr`.
+//!
+//! Use it with `#[serde(with = "tagged_tree::flat")]`.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use tagged_tree::Tree;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "tagged_tree::flat")]
+//!     files: Tree<String, u32>,
+//! }
+//!
+//! let mut files = Tree::new(0);
+//! files.entry("src".to_owned()).or_insert(1).add_child("lib.rs".to_owned(), 2);
+//! let json = serde_json::to_string(&Config { files }).unwrap();
+//! assert_eq!(json, r#"{"files":{"":0,"/src":1,"/src/lib.rs":2}}"#);
+//!
+//! let config: Config = serde_json::from_str(&json).unwrap();
+//! assert_eq!(*config.files[&"src".to_owned()].value(), 1);
+//! ```
+
+use crate::Tree;
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, marker::PhantomData, str::FromStr};
+use serde::{
+    de::{self, Deserialize, Deserializer, MapAccess, Visitor},
+    ser::{Serialize, SerializeMap, Serializer},
+};
+
+pub fn serialize<K, V, S>(
+    tree: &Tree<K, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    K: Ord + fmt::Display,
+    V: Serialize,
+    S: Serializer,
+{
+    let mut map =
+        serializer.serialize_map(Some(tree.iter_paths().count() + 1))?;
+    map.serialize_entry("", tree.value())?;
+    for (path, node) in tree.iter_paths() {
+        map.serialize_entry(&encode_path(&path), node.value())?;
+    }
+    map.end()
+}
+
+pub fn deserialize<'de, K, V, D>(
+    deserializer: D,
+) -> Result<Tree<K, V>, D::Error>
+where
+    K: Ord + FromStr,
+    K::Err: fmt::Display,
+    V: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_map(FlatVisitor(PhantomData))
+}
+
+struct FlatVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K, V> Visitor<'de> for FlatVisitor<K, V>
+where
+    K: Ord + FromStr,
+    K::Err: fmt::Display,
+    V: Deserialize<'de>,
+{
+    type Value = Tree<K, V>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of paths to values")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> Result<Self::Value, A::Error> {
+        let mut entries = BTreeMap::new();
+        while let Some(pointer) = map.next_key::<String>()? {
+            let path = decode_path::<K>(&pointer).map_err(de::Error::custom)?;
+            let value: V = map.next_value()?;
+            if entries.insert(path, (pointer.clone(), value)).is_some() {
+                return Err(de::Error::custom(format_args!(
+                    "duplicate path `{}`",
+                    pointer
+                )));
+            }
+        }
+
+        // paths are sorted, so every node comes after its ancestors
+        let mut entries = entries.into_iter();
+        let mut tree = match entries.next() {
+            Some((path, (_, value))) if path.is_empty() => Tree::new(value),
+            _ => return Err(de::Error::custom("missing the root path ``")),
+        };
+        for (mut path, (pointer, value)) in entries {
+            let key = path.pop().expect("only the root has an empty path");
+            let parent = path
+                .iter()
+                .try_fold(&mut tree, |node, key| node.get_child_mut(key));
+            match parent {
+                Some(parent) => {
+                    parent.add_child(key, value);
+                }
+                None => {
+                    return Err(de::Error::custom(format_args!(
+                        "missing ancestor for path `{}`",
+                        pointer
+                    )))
+                }
+            }
+        }
+        Ok(tree)
+    }
+}
+
+fn encode_path<K: fmt::Display>(path: &[&K]) -> String {
+    let mut pointer = String::new();
+    for key in path {
+        pointer.push('/');
+        pointer
+            .push_str(&key.to_string().replace('~', "~0").replace('/', "~1"));
+    }
+    pointer
+}
+
+fn decode_path<K>(pointer: &str) -> Result<Vec<K>, String>
+where
+    K: FromStr,
+    K::Err: fmt::Display,
+{
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    if !pointer.starts_with('/') {
+        return Err(format!("path `{}` doesn't start with `/`", pointer));
+    }
+
+    pointer[1..]
+        .split('/')
+        .map(|segment| {
+            let mut key = String::with_capacity(segment.len());
+            let mut chars = segment.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '~' => match chars.next() {
+                        Some('0') => key.push('~'),
+                        Some('1') => key.push('/'),
+                        _ => {
+                            return Err(format!(
+                                "invalid escape sequence in path `{}`",
+                                pointer
+                            ))
+                        }
+                    },
+                    _ => key.push(c),
+                }
+            }
+            key.parse().map_err(|err| {
+                format!("invalid key `{}` in path `{}`: {}", key, pointer, err)
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,187 @@
+//! Reads directories into trees, and writes trees back as directories.
+//!
+//! ```
+//! use std::ffi::OsString;
+//! use tagged_tree::{fs::DirOptions, Tree};
+//!
+//! # let dir = tempfile::tempdir().unwrap();
+//! let mut files = Tree::new(Vec::new());
+//! files
+//!     .entry("src".to_owned())
+//!     .or_insert(Vec::new())
+//!     .add_child("lib.rs".to_owned(), b"pub mod fs;".to_vec());
+//! files.write_to_dir(dir.path()).unwrap();
+//!
+//! let tree = Tree::from_dir(dir.path(), &DirOptions::new()).unwrap();
+//! let src = tree.get_child(&OsString::from("src")).unwrap();
+//! assert_eq!(src[&OsString::from("lib.rs")].value().len(), 11);
+//! ```
+
+use crate::{glob::GlobSet, Tree};
+use std::{
+    ffi::OsString,
+    fs::{self, Metadata},
+    io,
+    path::{Component, Path, PathBuf},
+};
+
+/// Controls how a directory is read by [`Tree::from_dir`]
+#[derive(Debug, Clone, Default)]
+pub struct DirOptions {
+    follow_symlinks: bool,
+    max_depth: Option<usize>,
+    ignore: GlobSet,
+}
+
+impl DirOptions {
+    #[inline]
+    pub fn new() -> Self {
+        DirOptions::default()
+    }
+
+    /// Reads the targets of symbolic links instead of the links themselves.
+    /// Links leading back to one of their own ancestors aren't followed.
+    #[inline]
+    pub fn follow_symlinks(mut self, follow: bool) -> Self {
+        self.follow_symlinks = follow;
+        self
+    }
+
+    /// Leaves out the entries below the given depth, the directory itself
+    /// being at depth zero
+    #[inline]
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Leaves out the entries whose path, relative to the directory, matches
+    /// any of the patterns, along with everything under them
+    #[inline]
+    pub fn ignore(mut self, patterns: GlobSet) -> Self {
+        self.ignore = patterns;
+        self
+    }
+}
+
+impl Tree<OsString, Metadata> {
+    /// Reads a directory, keyed by file name, with the metadata of each
+    /// entry as its value
+    pub fn from_dir(
+        path: impl AsRef<Path>,
+        options: &DirOptions,
+    ) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut tree = Tree::new(metadata(path, options.follow_symlinks)?);
+
+        // the keys leading to each directory still to be read, along with the
+        // canonical paths of the directories containing it
+        let mut stack = Vec::new();
+        if tree.value.is_dir() {
+            stack.push((Vec::new(), path.to_owned(), Vec::new()));
+        }
+        while let Some((keys, dir, mut ancestors)) = stack.pop() {
+            if options.max_depth.is_some_and(|max| keys.len() >= max) {
+                continue;
+            }
+            if options.follow_symlinks {
+                let canonical = fs::canonicalize(&dir)?;
+                if ancestors.contains(&canonical) {
+                    continue;
+                }
+                ancestors.push(canonical);
+            }
+
+            let node = keys
+                .iter()
+                .try_fold(&mut tree, |node, key| node.get_child_mut(key))
+                .expect("directories are added before being read");
+            for entry in fs::read_dir(&dir)? {
+                let entry = entry?;
+                let name = entry.file_name();
+                let mut child_keys = keys.clone();
+                child_keys.push(name.clone());
+                if !options.ignore.is_empty()
+                    && !options
+                        .ignore
+                        .matches(child_keys.iter().map(|k| k.to_string_lossy()))
+                        .is_empty()
+                {
+                    continue;
+                }
+
+                let metadata =
+                    metadata(&entry.path(), options.follow_symlinks)?;
+                if metadata.is_dir() {
+                    stack.push((child_keys, entry.path(), ancestors.clone()));
+                }
+                node.add_child(name, metadata);
+            }
+        }
+        Ok(tree)
+    }
+}
+
+/// Follows links if asked to, falling back to the link itself when it's
+/// broken
+fn metadata(path: &Path, follow_symlinks: bool) -> io::Result<Metadata> {
+    if follow_symlinks {
+        if let Ok(metadata) = fs::metadata(path) {
+            return Ok(metadata);
+        }
+    }
+    fs::symlink_metadata(path)
+}
+
+impl Tree<String, Vec<u8>> {
+    /// Writes the tree into a directory, creating it if needed. Childless
+    /// nodes become files with their value as contents, and the other nodes
+    /// become directories.
+    ///
+    /// Fails without writing anything if a key isn't a plain file name, or if
+    /// a node with children has a value, since directories can't have
+    /// contents.
+    pub fn write_to_dir(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let nodes =
+            || std::iter::once((Vec::new(), self)).chain(self.iter_paths());
+
+        for (keys, node) in nodes() {
+            if let Some(key) = keys.last() {
+                let mut components = Path::new(key.as_str()).components();
+                match (components.next(), components.next()) {
+                    (Some(Component::Normal(name)), None)
+                        if name.to_str() == Some(key.as_str()) => {}
+                    _ => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("`{}` isn't a file name", key),
+                        ))
+                    }
+                }
+            }
+            if !node.is_childless() && !node.value.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "`{}` has children, so it can't have contents",
+                        keys.iter()
+                            .fold(PathBuf::new(), |p, k| p.join(k))
+                            .display()
+                    ),
+                ));
+            }
+        }
+
+        fs::create_dir_all(path)?;
+        for (keys, node) in nodes().skip(1) {
+            let path = keys.iter().fold(path.to_owned(), |p, k| p.join(k));
+            if node.is_childless() {
+                fs::write(path, &node.value)?;
+            } else {
+                fs::create_dir_all(path)?;
+            }
+        }
+        Ok(())
+    }
+}
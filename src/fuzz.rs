@@ -0,0 +1,92 @@
+//! Random trees for fuzzers and property tests, with at most [`MAX_DEPTH`]
+//! levels below the root and [`MAX_CHILDREN`] children per node.
+
+use crate::Tree;
+
+const MAX_DEPTH: usize = 4;
+const MAX_CHILDREN: usize = 6;
+
+#[cfg(feature = "arbitrary")]
+fn arbitrary_node<'a, K, V>(
+    u: &mut arbitrary::Unstructured<'a>,
+    depth: usize,
+) -> arbitrary::Result<Tree<K, V>>
+where
+    K: arbitrary::Arbitrary<'a> + Ord,
+    V: arbitrary::Arbitrary<'a>,
+{
+    let mut tree = Tree::new(V::arbitrary(u)?);
+    if depth < MAX_DEPTH {
+        for _ in 0..u.int_in_range(0..=MAX_CHILDREN)? {
+            let key = K::arbitrary(u)?;
+            let child = arbitrary_node(u, depth + 1)?;
+            tree.children.entry(key).or_insert(child);
+        }
+    }
+    Ok(tree)
+}
+
+/// Reads trees from unstructured data, for fuzz targets
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for Tree<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Ord,
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(
+        u: &mut arbitrary::Unstructured<'a>,
+    ) -> arbitrary::Result<Self> {
+        arbitrary_node(u, 0)
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+fn quickcheck_node<K, V>(g: &mut quickcheck::Gen, depth: usize) -> Tree<K, V>
+where
+    K: quickcheck::Arbitrary + Ord,
+    V: quickcheck::Arbitrary,
+{
+    let mut tree = Tree::new(V::arbitrary(g));
+    if depth < MAX_DEPTH {
+        let max = g.size().min(MAX_CHILDREN);
+        let count = <usize as quickcheck::Arbitrary>::arbitrary(g) % (max + 1);
+        for _ in 0..count {
+            let key = K::arbitrary(g);
+            let child = quickcheck_node(g, depth + 1);
+            tree.children.entry(key).or_insert(child);
+        }
+    }
+    tree
+}
+
+/// Generates trees sized by the generator, and shrinks them by removing one
+/// subtree at a time, the ones closest to the root first
+#[cfg(feature = "quickcheck")]
+impl<K, V> quickcheck::Arbitrary for Tree<K, V>
+where
+    K: quickcheck::Arbitrary + Ord,
+    V: quickcheck::Arbitrary,
+{
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        quickcheck_node(g, 0)
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let mut paths: Vec<Vec<K>> = self
+            .iter_paths()
+            .map(|(path, _)| path.into_iter().cloned().collect())
+            .collect();
+        paths.sort_by_key(Vec::len);
+
+        let tree = self.clone();
+        Box::new(paths.into_iter().map(move |mut path| {
+            let mut tree = tree.clone();
+            let key = path.pop().expect("the root isn't a candidate");
+            path.iter()
+                .try_fold(&mut tree, |node, key| node.get_child_mut(key))
+                .expect("the path was taken from the tree")
+                .remove(&key);
+            tree
+        }))
+    }
+}
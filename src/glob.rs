@@ -0,0 +1,539 @@
+//! Glob-style matching over the key paths of string-keyed trees.
+//!
+//! A pattern is a `/` separated list of segments, each segment being matched
+//! against one key. The following syntax is supported:
+//!
+//! - `?` matches any single character
+//! - `*` matches any sequence of characters within a single key
+//! - `**` as a whole segment matches any number of keys, including none
+//!   unless it is the last segment
+//! - `[abc]`, `[a-z]` and `[!a-z]` match a single character from a class
+//! - `{a,b}` matches either of the comma separated alternatives
+//! - `\` escapes the next character
+
+use crate::Tree;
+use alloc::{borrow::ToOwned, format, string::String, vec, vec::Vec};
+use core::{fmt, str::FromStr};
+
+/// A compiled glob pattern
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    source: String,
+    alternatives: Vec<Vec<Segment>>,
+}
+
+impl Pattern {
+    pub fn new(pattern: &str) -> Result<Self, PatternError> {
+        let alternatives = expand_braces(pattern)
+            .map_err(|message| PatternError::new(pattern, message))?
+            .iter()
+            .map(|alternative| parse_segments(alternative))
+            .collect::<Result<_, _>>()
+            .map_err(|message| PatternError::new(pattern, message))?;
+
+        Ok(Pattern {
+            source: pattern.to_owned(),
+            alternatives,
+        })
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Checks if the pattern matches the given key path
+    pub fn matches<I>(&self, path: I) -> bool
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let patterns = core::slice::from_ref(self);
+        let mut states = start(patterns);
+        for key in path {
+            states = step(patterns, &states, key.as_ref());
+            if states.is_empty() {
+                return false;
+            }
+        }
+        !matched(patterns, &states).is_empty()
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = PatternError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Pattern::new(s)
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// A set of patterns that can be matched against a tree in a single pass
+#[derive(Debug, Clone, Default)]
+pub struct GlobSet {
+    patterns: Vec<Pattern>,
+}
+
+impl GlobSet {
+    pub fn new<I>(patterns: I) -> Result<Self, PatternError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        patterns
+            .into_iter()
+            .map(|pattern| Pattern::new(pattern.as_ref()))
+            .collect()
+    }
+
+    #[inline]
+    pub fn patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    #[inline]
+    pub fn push(&mut self, pattern: Pattern) {
+        self.patterns.push(pattern);
+    }
+
+    /// Returns the indices of the patterns that match the given key path
+    pub fn matches<I>(&self, path: I) -> Vec<usize>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut states = start(&self.patterns);
+        for key in path {
+            states = step(&self.patterns, &states, key.as_ref());
+            if states.is_empty() {
+                return Vec::new();
+            }
+        }
+        matched(&self.patterns, &states)
+    }
+}
+
+impl core::iter::FromIterator<Pattern> for GlobSet {
+    fn from_iter<I: IntoIterator<Item = Pattern>>(iter: I) -> Self {
+        GlobSet {
+            patterns: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PatternError {
+    pattern: String,
+    message: &'static str,
+}
+
+impl PatternError {
+    fn new(pattern: &str, message: &'static str) -> Self {
+        PatternError {
+            pattern: pattern.to_owned(),
+            message,
+        }
+    }
+
+    #[inline]
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid glob pattern `{}`: {}",
+            self.pattern, self.message
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PatternError {}
+
+impl<K: Ord + AsRef<str>, V> Tree<K, V> {
+    /// An iterator visiting the nodes whose key path matches the pattern in
+    /// depth first order. The root is never visited.
+    pub fn glob(&self, pattern: &str) -> Result<Glob<'_, K, V>, PatternError> {
+        let set = GlobSet {
+            patterns: vec![Pattern::new(pattern)?],
+        };
+        let walker = Walker::new(self, &set.patterns);
+        Ok(Glob { set, walker })
+    }
+
+    /// An iterator visiting the nodes whose key path matches any pattern in the
+    /// set, along with the indices of the matching patterns
+    pub fn glob_set<'a, 's>(
+        &'a self,
+        set: &'s GlobSet,
+    ) -> GlobSetIter<'a, 's, K, V> {
+        GlobSetIter {
+            set,
+            walker: Walker::new(self, &set.patterns),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Glob<'a, K: Ord, V> {
+    set: GlobSet,
+    walker: Walker<'a, K, V>,
+}
+
+impl<'a, K: Ord + AsRef<str>, V> Iterator for Glob<'a, K, V> {
+    type Item = (Vec<&'a K>, &'a Tree<K, V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.walker
+            .next(&self.set.patterns)
+            .map(|(path, tree, _)| (path, tree))
+    }
+}
+
+impl<K: Ord + AsRef<str>, V> core::iter::FusedIterator for Glob<'_, K, V> {}
+
+#[derive(Debug)]
+pub struct GlobSetIter<'a, 's, K: Ord, V> {
+    set: &'s GlobSet,
+    walker: Walker<'a, K, V>,
+}
+
+impl<'a, K: Ord + AsRef<str>, V> Iterator for GlobSetIter<'a, '_, K, V> {
+    type Item = (Vec<&'a K>, &'a Tree<K, V>, Vec<usize>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.walker.next(&self.set.patterns)
+    }
+}
+
+impl<K: Ord + AsRef<str>, V> core::iter::FusedIterator
+    for GlobSetIter<'_, '_, K, V>
+{
+}
+
+/// Walks the tree depth first, only descending into nodes that can still
+/// lead to a match
+#[derive(Debug)]
+struct Walker<'a, K: Ord, V> {
+    stack: Vec<Frame<'a, K, V>>,
+}
+
+impl<'a, K: Ord + AsRef<str>, V> Walker<'a, K, V> {
+    fn new(tree: &'a Tree<K, V>, patterns: &[Pattern]) -> Self {
+        Walker {
+            stack: vec![(Vec::new(), tree, start(patterns))],
+        }
+    }
+
+    fn next(&mut self, patterns: &[Pattern]) -> Option<Match<'a, K, V>> {
+        while let Some((path, tree, states)) = self.stack.pop() {
+            for (key, child) in tree.iter_single().rev() {
+                let next = step(patterns, &states, key.as_ref());
+                if !next.is_empty() {
+                    let mut path = path.clone();
+                    path.push(key);
+                    self.stack.push((path, child, next));
+                }
+            }
+
+            if path.is_empty() {
+                continue;
+            }
+            let matched = matched(patterns, &states);
+            if !matched.is_empty() {
+                return Some((path, tree, matched));
+            }
+        }
+        None
+    }
+}
+
+/// A node along with its key path and the pattern states leading to it
+type Frame<'a, K, V> = (Vec<&'a K>, &'a Tree<K, V>, Vec<State>);
+
+/// A matching node along with its key path and the matching patterns
+type Match<'a, K, V> = (Vec<&'a K>, &'a Tree<K, V>, Vec<usize>);
+
+/// The position in a pattern: pattern index, alternative index and segment
+/// index
+type State = (usize, usize, usize);
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Segment {
+    AnyDepth,
+    Tokens(Vec<Token>),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Token {
+    Literal(char),
+    AnyChar,
+    AnySequence,
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+fn start(patterns: &[Pattern]) -> Vec<State> {
+    let states = patterns
+        .iter()
+        .enumerate()
+        .flat_map(|(p, pattern)| {
+            (0..pattern.alternatives.len()).map(move |a| (p, a, 0))
+        })
+        .collect();
+    closure(patterns, states)
+}
+
+fn step(patterns: &[Pattern], states: &[State], key: &str) -> Vec<State> {
+    let mut next = Vec::new();
+    for &(p, a, s) in states {
+        match patterns[p].alternatives[a].get(s) {
+            None => {}
+            Some(Segment::AnyDepth) => {
+                next.push((p, a, s));
+                next.push((p, a, s + 1));
+            }
+            Some(Segment::Tokens(tokens)) if match_segment(tokens, key) => {
+                next.push((p, a, s + 1))
+            }
+            Some(Segment::Tokens(_)) => {}
+        }
+    }
+    closure(patterns, next)
+}
+
+/// Adds the states reachable by letting `**` match zero keys. A trailing `**`
+/// must match at least one key.
+fn closure(patterns: &[Pattern], mut states: Vec<State>) -> Vec<State> {
+    let mut i = 0;
+    while i < states.len() {
+        let (p, a, s) = states[i];
+        let segments = &patterns[p].alternatives[a];
+        if segments.get(s) == Some(&Segment::AnyDepth) && s + 1 < segments.len()
+        {
+            states.push((p, a, s + 1));
+        }
+        i += 1;
+    }
+    states.sort_unstable();
+    states.dedup();
+    states
+}
+
+fn matched(patterns: &[Pattern], states: &[State]) -> Vec<usize> {
+    let mut matched: Vec<usize> = states
+        .iter()
+        .filter(|(p, a, s)| patterns[*p].alternatives[*a].len() == *s)
+        .map(|(p, _, _)| *p)
+        .collect();
+    matched.dedup();
+    matched
+}
+
+fn match_segment(tokens: &[Token], key: &str) -> bool {
+    let chars: Vec<char> = key.chars().collect();
+    let (mut t, mut c) = (0, 0);
+    let mut backtrack = None;
+
+    while c < chars.len() {
+        match tokens.get(t) {
+            Some(Token::AnySequence) => {
+                backtrack = Some((t, c));
+                t += 1;
+                continue;
+            }
+            Some(token) if token.matches(chars[c]) => {
+                t += 1;
+                c += 1;
+                continue;
+            }
+            _ => {}
+        }
+        match backtrack {
+            Some((bt, bc)) => {
+                backtrack = Some((bt, bc + 1));
+                t = bt + 1;
+                c = bc + 1;
+            }
+            None => return false,
+        }
+    }
+
+    tokens[t..].iter().all(|token| *token == Token::AnySequence)
+}
+
+impl Token {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Token::Literal(literal) => *literal == c,
+            Token::AnyChar => true,
+            Token::AnySequence => false,
+            Token::Class { negated, ranges } => {
+                ranges.iter().any(|(lo, hi)| (*lo..=*hi).contains(&c))
+                    != *negated
+            }
+        }
+    }
+}
+
+/// Expands brace alternations into a list of patterns without braces
+fn expand_braces(pattern: &str) -> Result<Vec<String>, &'static str> {
+    let chars: Vec<char> = pattern.chars().collect();
+
+    let mut open = None;
+    let mut depth = 0;
+    let mut commas = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '{' => {
+                if depth == 0 {
+                    open = Some(i);
+                }
+                depth += 1;
+            }
+            ',' if depth == 1 => commas.push(i),
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            '}' => return Err("unmatched `}`"),
+            _ => {}
+        }
+        i += 1;
+    }
+
+    let open = match open {
+        None => return Ok(vec![pattern.to_owned()]),
+        Some(_) if depth > 0 => return Err("unmatched `{`"),
+        Some(open) => open,
+    };
+    let close = i;
+
+    let prefix: String = chars[..open].iter().collect();
+    let suffix: String = chars[close + 1..].iter().collect();
+    let mut bounds = vec![open];
+    bounds.extend(commas);
+    bounds.push(close);
+
+    let mut expanded = Vec::new();
+    for window in bounds.windows(2) {
+        let alternative: String =
+            chars[window[0] + 1..window[1]].iter().collect();
+        expanded.extend(expand_braces(&format!(
+            "{}{}{}",
+            prefix, alternative, suffix
+        ))?);
+    }
+    Ok(expanded)
+}
+
+fn parse_segments(pattern: &str) -> Result<Vec<Segment>, &'static str> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                current.push(c);
+                current.push(chars.next().ok_or("trailing `\\`")?);
+            }
+            '/' => {
+                segments.push(parse_segment(&core::mem::take(&mut current))?)
+            }
+            _ => current.push(c),
+        }
+    }
+    segments.push(parse_segment(&current)?);
+    Ok(segments)
+}
+
+fn parse_segment(segment: &str) -> Result<Segment, &'static str> {
+    if segment.is_empty() {
+        return Err("empty path segment");
+    }
+    if segment == "**" {
+        return Ok(Segment::AnyDepth);
+    }
+
+    let mut tokens = Vec::new();
+    let mut chars = segment.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\\' => Token::Literal(chars.next().ok_or("trailing `\\`")?),
+            '?' => Token::AnyChar,
+            '*' => {
+                if tokens.last() == Some(&Token::AnySequence) {
+                    continue;
+                }
+                Token::AnySequence
+            }
+            '[' => {
+                let negated = matches!(chars.peek(), Some('!') | Some('^'));
+                if negated {
+                    chars.next();
+                }
+
+                let mut ranges = Vec::new();
+                let mut first = true;
+                loop {
+                    let lo = match chars.next() {
+                        None => return Err("unclosed character class"),
+                        Some(']') if !first => break,
+                        Some('\\') => {
+                            chars.next().ok_or("unclosed character class")?
+                        }
+                        Some(lo) => lo,
+                    };
+                    first = false;
+
+                    let mut lookahead = chars.clone();
+                    let hi = match (lookahead.next(), lookahead.next()) {
+                        (Some('-'), Some(hi)) if hi != ']' => {
+                            chars.next();
+                            chars.next();
+                            hi
+                        }
+                        _ => lo,
+                    };
+                    if hi < lo {
+                        return Err("invalid range in character class");
+                    }
+                    ranges.push((lo, hi));
+                }
+
+                Token::Class { negated, ranges }
+            }
+            _ => Token::Literal(c),
+        };
+        tokens.push(token);
+    }
+    Ok(Segment::Tokens(tokens))
+}
@@ -0,0 +1,181 @@
+//! Trait implementations that walk the tree with an explicit stack instead of
+//! recursing, so degenerate trees don't overflow the call stack.
+
+use crate::Tree;
+use alloc::{
+    collections::{btree_map, BTreeMap},
+    vec,
+    vec::Vec,
+};
+use core::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    mem::{self, ManuallyDrop},
+    ptr,
+};
+
+impl<K: Ord, V> Tree<K, V> {
+    /// Splits the tree into its value and children, which can't be moved
+    /// out of it directly because of the `Drop` implementation
+    pub(crate) fn into_parts(self) -> (V, BTreeMap<K, Self>) {
+        // SAFETY: the closure returns the fields of the tree
+        unsafe {
+            take_parts(self, |tree| (&mut tree.value, &mut tree.children))
+        }
+    }
+
+    /// Compares two trees the way a derived implementation would: values
+    /// first, then children in key order
+    fn compare_with<F>(&self, other: &Self, mut compare: F) -> Option<Ordering>
+    where
+        F: FnMut(&V, &V) -> Option<Ordering>,
+    {
+        match compare(&self.value, &other.value)? {
+            Ordering::Equal => {}
+            ordering => return Some(ordering),
+        }
+
+        let mut stack = vec![(self.children.iter(), other.children.iter())];
+        while let Some((left, right)) = stack.last_mut() {
+            let (left, right) = match (left.next(), right.next()) {
+                (None, None) => {
+                    stack.pop();
+                    continue;
+                }
+                (None, Some(_)) => return Some(Ordering::Less),
+                (Some(_), None) => return Some(Ordering::Greater),
+                (Some(left), Some(right)) => (left, right),
+            };
+            match left.0.cmp(right.0) {
+                Ordering::Equal => {}
+                ordering => return Some(ordering),
+            }
+            match compare(&left.1.value, &right.1.value)? {
+                Ordering::Equal => {}
+                ordering => return Some(ordering),
+            }
+            stack.push((left.1.children.iter(), right.1.children.iter()));
+        }
+        Some(Ordering::Equal)
+    }
+}
+
+/// Moves the value and children out of a node whose type implements `Drop`,
+/// leaving nothing behind to be dropped.
+///
+/// # Safety
+///
+/// `fields` must return references to the value and children of `node`.
+pub(crate) unsafe fn take_parts<T, V, C: Default>(
+    node: T,
+    fields: impl FnOnce(&mut T) -> (&mut V, &mut C),
+) -> (V, C) {
+    let mut node = ManuallyDrop::new(node);
+    let (value, children) = fields(&mut node);
+    let children = mem::take(children);
+    // SAFETY: the node is never used or dropped again, and the value is read
+    // exactly once
+    let value = unsafe { ptr::read(value) };
+    (value, children)
+}
+
+impl<K: Ord, V> Drop for Tree<K, V> {
+    fn drop(&mut self) {
+        if self.children.is_empty() {
+            return;
+        }
+
+        // detach the children of every node before it is dropped, so no drop
+        // recurses more than one level
+        let mut stack = vec![mem::take(&mut self.children)];
+        while let Some(children) = stack.pop() {
+            for mut child in children.into_values() {
+                if !child.children.is_empty() {
+                    stack.push(mem::take(&mut child.children));
+                }
+            }
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Clone for Tree<K, V> {
+    fn clone(&self) -> Self {
+        type Frame<'a, K, V> =
+            (Option<K>, Tree<K, V>, btree_map::Iter<'a, K, Tree<K, V>>);
+
+        let mut stack: Vec<Frame<'_, K, V>> =
+            vec![(None, Tree::new(self.value.clone()), self.children.iter())];
+        loop {
+            let (_, _, children) = stack.last_mut().expect("the root is last");
+            if let Some((key, child)) = children.next() {
+                stack.push((
+                    Some(key.clone()),
+                    Tree::new(child.value.clone()),
+                    child.children.iter(),
+                ));
+                continue;
+            }
+
+            let (key, tree, _) = stack.pop().expect("the root is last");
+            match (key, stack.last_mut()) {
+                (Some(key), Some((_, parent, _))) => {
+                    parent.children.insert(key, tree);
+                }
+                _ => return tree,
+            }
+        }
+    }
+}
+
+impl<K: Ord, V: PartialEq> PartialEq for Tree<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        let mut stack = vec![(self, other)];
+        while let Some((left, right)) = stack.pop() {
+            if left.value != right.value
+                || left.children.len() != right.children.len()
+            {
+                return false;
+            }
+            for ((left_key, left), (right_key, right)) in
+                left.children.iter().zip(&right.children)
+            {
+                if left_key != right_key {
+                    return false;
+                }
+                stack.push((left, right));
+            }
+        }
+        true
+    }
+}
+
+impl<K: Ord, V: Eq> Eq for Tree<K, V> {}
+
+impl<K: Ord, V: PartialOrd> PartialOrd for Tree<K, V> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.compare_with(other, V::partial_cmp)
+    }
+}
+
+impl<K: Ord, V: Ord> Ord for Tree<K, V> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.compare_with(other, |left, right| Some(left.cmp(right)))
+            .expect("values are totally ordered")
+    }
+}
+
+impl<K: Ord + Hash, V: Hash> Hash for Tree<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut stack = vec![self];
+        while let Some(tree) = stack.pop() {
+            tree.value.hash(state);
+            state.write_usize(tree.children.len());
+            for (key, child) in &tree.children {
+                key.hash(state);
+                stack.push(child);
+            }
+        }
+    }
+}
@@ -1,8 +1,6 @@
 use crate::Tree;
-use std::{
-    collections::{btree_map, VecDeque},
-    iter::FusedIterator,
-};
+use alloc::collections::{btree_map, VecDeque};
+use core::iter::FusedIterator;
 
 #[derive(Clone, Debug)]
 pub struct BreadthFirstIter<'a, K: Ord + 'a, V: 'a> {
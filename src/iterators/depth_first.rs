@@ -1,8 +1,10 @@
 use crate::Tree;
-use std::{
+use alloc::{
     collections::btree_map::{self, BTreeMap},
-    iter::FusedIterator,
+    vec,
+    vec::Vec,
 };
+use core::iter::FusedIterator;
 
 #[derive(Clone, Debug)]
 pub struct DepthFirstIter<'a, K: Ord + 'a, V: 'a> {
@@ -0,0 +1,126 @@
+use super::PathIter;
+use crate::Tree;
+use alloc::{vec, vec::Vec};
+use core::iter::FusedIterator;
+
+/// Controls which nodes are visited when flattening a tree
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FlatOptions {
+    include_root: bool,
+    include_internal: bool,
+}
+
+impl FlatOptions {
+    #[inline]
+    pub fn new() -> Self {
+        FlatOptions::default()
+    }
+
+    /// Visits the root, with an empty path. Off by default.
+    #[inline]
+    pub fn include_root(mut self, include: bool) -> Self {
+        self.include_root = include;
+        self
+    }
+
+    /// Visits the nodes that have children, the root included. On by
+    /// default, turn it off to only visit the leaves.
+    #[inline]
+    pub fn include_internal(mut self, include: bool) -> Self {
+        self.include_internal = include;
+        self
+    }
+
+    #[inline]
+    fn includes<K: Ord, V>(&self, tree: &Tree<K, V>, is_root: bool) -> bool {
+        (!is_root || self.include_root)
+            && (self.include_internal || tree.is_childless())
+    }
+}
+
+impl Default for FlatOptions {
+    #[inline]
+    fn default() -> Self {
+        FlatOptions {
+            include_root: false,
+            include_internal: true,
+        }
+    }
+}
+
+/// Visits the values in depth first order along with the keys leading to them
+#[derive(Clone, Debug)]
+pub struct FlatIter<'a, K: Ord + 'a, V: 'a> {
+    root: Option<&'a Tree<K, V>>,
+    paths: PathIter<'a, K, V>,
+    options: FlatOptions,
+}
+
+impl<'a, K: Ord + 'a, V: 'a> FlatIter<'a, K, V> {
+    #[inline]
+    pub(crate) fn new(tree: &'a Tree<K, V>, options: FlatOptions) -> Self {
+        Self {
+            root: Some(tree),
+            paths: PathIter::new(tree),
+            options,
+        }
+    }
+}
+
+impl<'a, K: Ord + 'a, V: 'a> Iterator for FlatIter<'a, K, V> {
+    type Item = (Vec<&'a K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            if self.options.includes(root, true) {
+                return Some((Vec::new(), &root.value));
+            }
+        }
+        let options = self.options;
+        self.paths
+            .by_ref()
+            .find(|(_, tree)| options.includes(tree, false))
+            .map(|(path, tree)| (path, &tree.value))
+    }
+}
+
+impl<K: Ord, V> FusedIterator for FlatIter<'_, K, V> {}
+
+/// Takes the values in depth first order along with the keys leading to them
+#[derive(Debug)]
+pub struct IntoFlat<K: Ord, V> {
+    stack: Vec<(Vec<K>, Tree<K, V>)>,
+    options: FlatOptions,
+}
+
+impl<K: Ord + Clone, V> IntoFlat<K, V> {
+    #[inline]
+    pub(crate) fn new(tree: Tree<K, V>, options: FlatOptions) -> Self {
+        Self {
+            stack: vec![(Vec::new(), tree)],
+            options,
+        }
+    }
+}
+
+impl<K: Ord + Clone, V> Iterator for IntoFlat<K, V> {
+    type Item = (Vec<K>, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((path, tree)) = self.stack.pop() {
+            let included = self.options.includes(&tree, path.is_empty());
+            let (value, children) = tree.into_parts();
+            for (key, child) in children.into_iter().rev() {
+                let mut path = path.clone();
+                path.push(key);
+                self.stack.push((path, child));
+            }
+            if included {
+                return Some((path, value));
+            }
+        }
+        None
+    }
+}
+
+impl<K: Ord + Clone, V> FusedIterator for IntoFlat<K, V> {}
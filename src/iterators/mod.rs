@@ -1,5 +1,11 @@
 mod breadth_first;
 mod depth_first;
+mod flat;
+mod paths;
+mod prefix;
 
 pub use breadth_first::*;
 pub use depth_first::*;
+pub use flat::*;
+pub use paths::*;
+pub use prefix::*;
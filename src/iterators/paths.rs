@@ -0,0 +1,45 @@
+use crate::Tree;
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+
+/// Visits the nodes in depth first order along with the keys leading to them
+#[derive(Clone, Debug)]
+pub struct PathIter<'a, K: Ord + 'a, V: 'a> {
+    stack: Vec<(Vec<&'a K>, &'a Tree<K, V>)>,
+}
+
+impl<'a, K: Ord + 'a, V: 'a> PathIter<'a, K, V> {
+    #[inline]
+    pub(crate) fn new(tree: &'a Tree<K, V>) -> Self {
+        let mut iter = Self { stack: Vec::new() };
+        iter.push_children(&[], tree);
+        iter
+    }
+
+    fn push_children(&mut self, path: &[&'a K], tree: &'a Tree<K, V>) {
+        for (key, child) in tree.iter_single().rev() {
+            let mut path = path.to_vec();
+            path.push(key);
+            self.stack.push((path, child));
+        }
+    }
+
+    /// Leaves out the children of `tree`, which must be the node last returned
+    /// by `next`
+    pub(crate) fn skip_children(&mut self, tree: &'a Tree<K, V>) {
+        let len = self.stack.len() - tree.children.len();
+        self.stack.truncate(len);
+    }
+}
+
+impl<'a, K: Ord + 'a, V: 'a> Iterator for PathIter<'a, K, V> {
+    type Item = (Vec<&'a K>, &'a Tree<K, V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (path, tree) = self.stack.pop()?;
+        self.push_children(&path, tree);
+        Some((path, tree))
+    }
+}
+
+impl<K: Ord, V> FusedIterator for PathIter<'_, K, V> {}
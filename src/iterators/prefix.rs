@@ -0,0 +1,41 @@
+use crate::Tree;
+use alloc::collections::btree_map;
+use core::{borrow::Borrow, iter::FusedIterator, ops::Bound};
+
+#[derive(Clone, Debug)]
+pub struct PrefixIter<'a, 'p, K: Ord + 'a, V: 'a> {
+    range: btree_map::Range<'a, K, Tree<K, V>>,
+    prefix: &'p str,
+}
+
+impl<'a, 'p, K: Ord + Borrow<str> + 'a, V: 'a> PrefixIter<'a, 'p, K, V> {
+    #[inline]
+    pub(crate) fn new(tree: &'a Tree<K, V>, prefix: &'p str) -> Self {
+        Self {
+            range: tree.range_children::<str, _>((
+                Bound::Included(prefix),
+                Bound::Unbounded,
+            )),
+            prefix,
+        }
+    }
+}
+
+impl<'a, K: Ord + Borrow<str> + 'a, V: 'a> Iterator
+    for PrefixIter<'a, '_, K, V>
+{
+    type Item = (&'a K, &'a Tree<K, V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, tree) = self.range.next()?;
+        if key.borrow().starts_with(self.prefix) {
+            Some((key, tree))
+        } else {
+            // keys are sorted, so no later key can have the prefix either
+            self.range = btree_map::Range::default();
+            None
+        }
+    }
+}
+
+impl<K: Ord + Borrow<str>, V> FusedIterator for PrefixIter<'_, '_, K, V> {}
@@ -0,0 +1,446 @@
+//! Persists trees in an append only log, one record per node, so that saving
+//! a modified tree only writes the nodes that changed.
+//!
+//! Each record is keyed by the path of its node, made of every key on the
+//! path [encoded](crate::binary::Encode) and prefixed with its length, so the
+//! records of a subtree are next to each other in key order. The file starts
+//! with [`MAGIC`], followed by the records, each being:
+//!
+//! - the length of the path, as a little endian `u32`
+//! - the length of the value, as a little endian `u32`, or `u32::MAX` when
+//!   the node was removed
+//! - the encoded path
+//! - the encoded value
+//!
+//! Later records override earlier ones. Records that have been overridden
+//! keep taking space until the log is [compacted](KvStore::compact).
+//!
+//! The store keeps the latest encoded value of every node in memory, to tell
+//! which ones changed without reading the log back.
+//!
+//! ```
+//! use tagged_tree::{kv::KvStore, Tree};
+//!
+//! # let dir = tempfile::tempdir().unwrap();
+//! # let path = dir.path().join("tree.log");
+//! let mut tree = Tree::new(0u32);
+//! tree.entry("a".to_owned()).or_insert(1).add_child("b".to_owned(), 2);
+//!
+//! let mut store = KvStore::<String, u32>::open(&path).unwrap();
+//! assert_eq!(store.save_changes(&tree).unwrap(), 3);
+//!
+//! tree.add_child("c".to_owned(), 3);
+//! assert_eq!(store.save_changes(&tree).unwrap(), 1);
+//!
+//! let a = store.load_subtree(&["a".to_owned()]).unwrap().unwrap();
+//! assert_eq!(*a.get_child("b").unwrap().value(), 2);
+//! ```
+
+use crate::{
+    binary::{Decode, Encode},
+    Tree,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsString,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+/// The bytes every log starts with
+pub const MAGIC: &[u8; 4] = b"TTL\x01";
+
+/// The value length of a record removing a node
+const REMOVED: u32 = u32::MAX;
+
+/// A tree stored in an append only log
+pub struct KvStore<K, V> {
+    path: PathBuf,
+    file: File,
+    len: u64,
+    index: BTreeMap<Vec<u8>, Record>,
+    wasted: u64,
+    marker: PhantomData<fn() -> (K, V)>,
+}
+
+/// The latest value of a node, as it is encoded in the log
+struct Record {
+    value: Vec<u8>,
+}
+
+impl Record {
+    /// The size of the whole record in the log
+    #[inline]
+    fn size(&self, path: &[u8]) -> u64 {
+        8 + path.len() as u64 + self.value.len() as u64
+    }
+
+    #[inline]
+    fn decode<V: for<'a> Decode<'a>>(&self) -> io::Result<V> {
+        V::decode(&self.value).map_err(|err| invalid_data(err.message()))
+    }
+}
+
+/// The records written by a save, kept aside until they are synced
+struct Staged {
+    records: Vec<(Vec<u8>, Record)>,
+    removed: Vec<Vec<u8>>,
+    len: u64,
+}
+
+impl<K, V> KvStore<K, V>
+where
+    K: Ord + Encode + for<'a> Decode<'a>,
+    V: Encode + for<'a> Decode<'a>,
+{
+    /// Opens a log, creating it if it doesn't exist. A record that was only
+    /// partially written is dropped.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_owned();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)?;
+
+        let mut store = KvStore {
+            path,
+            file: file.try_clone()?,
+            len: MAGIC.len() as u64,
+            index: BTreeMap::new(),
+            wasted: 0,
+            marker: PhantomData,
+        };
+        let file_len = file.metadata()?.len();
+        if file_len == 0 {
+            file.write_all(MAGIC)?;
+            file.sync_data()?;
+            return Ok(store);
+        }
+
+        let mut reader = BufReader::new(file);
+        let mut magic = [0; 4];
+        if reader.read_exact(&mut magic).is_err() || &magic != MAGIC {
+            return Err(invalid_data("missing magic bytes"));
+        }
+        loop {
+            let mut header = [0; 8];
+            if !read_fully(&mut reader, &mut header)? {
+                break;
+            }
+            let path_len = u32_at(&header, 0) as usize;
+            let value_len = u32_at(&header, 4);
+            let record_len = 8
+                + path_len as u64
+                + if value_len == REMOVED {
+                    0
+                } else {
+                    u64::from(value_len)
+                };
+            // the lengths of a partial record can be anything, so they are
+            // checked before allocating
+            if store.len + record_len > file_len {
+                break;
+            }
+
+            let mut path = vec![0; path_len];
+            if !read_fully(&mut reader, &mut path)? {
+                break;
+            }
+            let end = store.len + 8 + path_len as u64;
+            if value_len == REMOVED {
+                store.wasted += end - store.len;
+                if let Some(old) = store.index.remove(&path) {
+                    store.wasted += old.size(&path);
+                }
+                store.len = end;
+                continue;
+            }
+
+            let mut value = vec![0; value_len as usize];
+            if !read_fully(&mut reader, &mut value)? {
+                break;
+            }
+            let record = Record { value };
+            store.len = end + u64::from(value_len);
+            if let Some(old) = store.index.get(&path) {
+                store.wasted += old.size(&path);
+            }
+            store.index.insert(path, record);
+        }
+
+        // drop whatever was left by an interrupted write
+        store.file.set_len(store.len)?;
+        Ok(store)
+    }
+
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The number of stored nodes
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// The bytes taken by records that have been overridden, which
+    /// [`compact`](KvStore::compact) would free
+    #[inline]
+    pub fn wasted_bytes(&self) -> u64 {
+        self.wasted
+    }
+
+    /// Writes the nodes that were added or changed since the last save, and
+    /// records the removal of the nodes that are gone. Values are compared
+    /// by their encoding. Returns the number of records written.
+    ///
+    /// The store only takes the new records into account once they are
+    /// synced to the disk. If saving fails, the log is cut back to where it
+    /// was.
+    pub fn save_changes(&mut self, tree: &Tree<K, V>) -> io::Result<usize> {
+        let staged = match self.write_changes(tree) {
+            Ok(staged) => staged,
+            Err(err) => {
+                let _ = self.file.set_len(self.len);
+                return Err(err);
+            }
+        };
+
+        let written = staged.records.len() + staged.removed.len();
+        for (path, record) in staged.records {
+            if let Some(old) = self.index.get(&path) {
+                self.wasted += old.size(&path);
+            }
+            self.index.insert(path, record);
+        }
+        for path in staged.removed {
+            let old = self.index.remove(&path).expect("the path is stored");
+            self.wasted += 8 + path.len() as u64 + old.size(&path);
+        }
+        self.len = staged.len;
+        Ok(written)
+    }
+
+    /// Appends and syncs the records of a save, leaving the index as is
+    fn write_changes(&self, tree: &Tree<K, V>) -> io::Result<Staged> {
+        let mut out = BufWriter::new(&self.file);
+        let mut staged = Staged {
+            records: Vec::new(),
+            removed: Vec::new(),
+            len: self.len,
+        };
+        let mut seen = BTreeSet::new();
+
+        let nodes =
+            std::iter::once((Vec::new(), tree)).chain(tree.iter_paths());
+        for (keys, node) in nodes {
+            let path = encode_path(keys)?;
+            let mut value = Vec::new();
+            node.value.encode(&mut value);
+            let unchanged = self
+                .index
+                .get(&path)
+                .is_some_and(|record| record.value == value);
+            if unchanged {
+                seen.insert(path);
+                continue;
+            }
+
+            let record = Record { value };
+            write_record(&mut out, &path, &record)?;
+            staged.len += record.size(&path);
+            seen.insert(path.clone());
+            staged.records.push((path, record));
+        }
+
+        staged.removed = self
+            .index
+            .keys()
+            .filter(|path| !seen.contains(*path))
+            .cloned()
+            .collect();
+        for path in &staged.removed {
+            out.write_all(&to_u32(path.len())?.to_le_bytes())?;
+            out.write_all(&REMOVED.to_le_bytes())?;
+            out.write_all(path)?;
+            staged.len += 8 + path.len() as u64;
+        }
+
+        out.into_inner().map_err(io::IntoInnerError::into_error)?;
+        self.file.sync_data()?;
+        Ok(staged)
+    }
+
+    /// Reads the whole tree, if one has been saved
+    #[inline]
+    pub fn load(&self) -> io::Result<Option<Tree<K, V>>> {
+        self.load_subtree(std::iter::empty())
+    }
+
+    /// Reads the subtree at the path, if there is one
+    pub fn load_subtree<'k, I>(&self, path: I) -> io::Result<Option<Tree<K, V>>>
+    where
+        I: IntoIterator<Item = &'k K>,
+        K: 'k,
+    {
+        let prefix = encode_path(path)?;
+        let mut records = self
+            .index
+            .range(prefix.clone()..)
+            .take_while(|(path, _)| path.starts_with(&prefix));
+
+        let mut tree = match records.next() {
+            Some((path, record)) if *path == prefix => {
+                Tree::new(record.decode()?)
+            }
+            _ => return Ok(None),
+        };
+        for (path, record) in records {
+            let mut keys = decode_path::<K>(&path[prefix.len()..])?;
+            let key = keys.pop().expect("only the root has an empty path");
+            let parent = keys
+                .iter()
+                .try_fold(&mut tree, |node, key| node.get_child_mut(key))
+                .ok_or_else(|| invalid_data("missing ancestor"))?;
+            parent.add_child(key, record.decode()?);
+        }
+        Ok(Some(tree))
+    }
+
+    /// Rewrites the log with only the latest records
+    pub fn compact(&mut self) -> io::Result<()> {
+        let mut temp = OsString::from(&self.path);
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+
+        let len = match self.write_compacted(&temp) {
+            Ok(len) => len,
+            Err(err) => {
+                let _ = fs::remove_file(&temp);
+                return Err(err);
+            }
+        };
+        if let Err(err) = fs::rename(&temp, &self.path) {
+            let _ = fs::remove_file(&temp);
+            return Err(err);
+        }
+        self.file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .open(&self.path)?;
+        self.len = len;
+        self.wasted = 0;
+        Ok(())
+    }
+
+    /// Writes the latest records to a new log, returning its length
+    fn write_compacted(&self, path: &Path) -> io::Result<u64> {
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(MAGIC)?;
+        let mut len = MAGIC.len() as u64;
+        for (path, record) in &self.index {
+            write_record(&mut out, path, record)?;
+            len += record.size(path);
+        }
+        out.into_inner()
+            .map_err(io::IntoInnerError::into_error)?
+            .sync_all()?;
+        Ok(len)
+    }
+}
+
+impl<K, V> fmt::Debug for KvStore<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KvStore")
+            .field("path", &self.path)
+            .field("len", &self.index.len())
+            .field("wasted_bytes", &self.wasted)
+            .finish()
+    }
+}
+
+fn write_record<W: Write>(
+    out: &mut W,
+    path: &[u8],
+    record: &Record,
+) -> io::Result<()> {
+    out.write_all(&to_u32(path.len())?.to_le_bytes())?;
+    out.write_all(&to_u32(record.value.len())?.to_le_bytes())?;
+    out.write_all(path)?;
+    out.write_all(&record.value)
+}
+
+/// Encodes the keys of a path, each prefixed with its length
+fn encode_path<'k, K, I>(keys: I) -> io::Result<Vec<u8>>
+where
+    K: Encode + 'k,
+    I: IntoIterator<Item = &'k K>,
+{
+    let mut path = Vec::new();
+    let mut key = Vec::new();
+    for k in keys {
+        key.clear();
+        k.encode(&mut key);
+        path.extend_from_slice(&to_u32(key.len())?.to_be_bytes());
+        path.extend_from_slice(&key);
+    }
+    Ok(path)
+}
+
+fn decode_path<K: for<'a> Decode<'a>>(mut path: &[u8]) -> io::Result<Vec<K>> {
+    let mut keys = Vec::new();
+    while !path.is_empty() {
+        if path.len() < 4 {
+            return Err(invalid_data("truncated path"));
+        }
+        let mut len = [0; 4];
+        len.copy_from_slice(&path[..4]);
+        let len = u32::from_be_bytes(len) as usize;
+        let key = path
+            .get(4..4 + len)
+            .ok_or_else(|| invalid_data("truncated path"))?;
+        keys.push(K::decode(key).map_err(|err| invalid_data(err.message()))?);
+        path = &path[4 + len..];
+    }
+    Ok(keys)
+}
+
+/// Fills the buffer, returning `false` if the reader ends first
+fn read_fully<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+#[inline]
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    let mut word = [0; 4];
+    word.copy_from_slice(&bytes[at..at + 4]);
+    u32::from_le_bytes(word)
+}
+
+fn to_u32(len: usize) -> io::Result<u32> {
+    if len >= REMOVED as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "record too large for the log",
+        ));
+    }
+    Ok(len as u32)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
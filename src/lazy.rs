@@ -0,0 +1,216 @@
+//! A tree whose children are loaded the first time they're needed, for
+//! browsing directories or large documents a node at a time.
+//!
+//! ```
+//! use std::cell::Cell;
+//! use tagged_tree::lazy::{LazyTree, Loader};
+//!
+//! struct Countdown {
+//!     loads: Cell<usize>,
+//! }
+//!
+//! impl Loader<u32, u32> for Countdown {
+//!     fn load_children(&self, _path: &[u32], value: &u32) -> Vec<(u32, u32)> {
+//!         self.loads.set(self.loads.get() + 1);
+//!         (0..*value).map(|key| (key, value - 1)).collect()
+//!     }
+//! }
+//!
+//! let mut tree = LazyTree::new(3, Countdown { loads: Cell::new(0) });
+//! assert!(!tree.is_expanded());
+//!
+//! let child = tree.get_child(&2).unwrap();
+//! assert_eq!(*child.value(), 2);
+//! assert_eq!(child.path(), [2]);
+//! assert!(tree.is_expanded() && !child.is_expanded());
+//!
+//! tree.get_child(&0).unwrap();
+//! assert_eq!(tree.loader().loads.get(), 1);
+//!
+//! tree.collapse();
+//! tree.expand_all(2);
+//! assert_eq!(tree.loader().loads.get(), 5);
+//! ```
+//!
+//! With the `stream` feature, the children can be loaded asynchronously
+//! instead, through a [`ChildLoader`](crate::stream::ChildLoader), while
+//! [streaming](crate::stream) the tree.
+
+use alloc::{
+    collections::btree_map::{self, BTreeMap},
+    rc::Rc,
+    vec,
+    vec::Vec,
+};
+use core::{borrow::Borrow, cell::OnceCell, fmt};
+
+/// Loads the children of the nodes of a [`LazyTree`]
+pub trait Loader<K, V> {
+    /// Loads the children of the node with the given value at the end of
+    /// `path`, the root being at the end of an empty one
+    ///
+    /// When a key is repeated, the last value is kept.
+    fn load_children(&self, path: &[K], value: &V) -> Vec<(K, V)>;
+}
+
+/// A tree whose nodes load their children on first access and keep them
+pub struct LazyTree<K: Ord, V, L> {
+    value: V,
+    path: Vec<K>,
+    loader: Rc<L>,
+    children: OnceCell<BTreeMap<K, LazyTree<K, V, L>>>,
+}
+
+impl<K: Ord, V, L> LazyTree<K, V, L> {
+    /// Creates a root whose children are loaded through `loader`
+    #[inline]
+    pub fn new(value: V, loader: L) -> Self {
+        Self::with_path(value, Vec::new(), Rc::new(loader))
+    }
+
+    #[inline]
+    fn with_path(value: V, path: Vec<K>, loader: Rc<L>) -> Self {
+        LazyTree {
+            value,
+            path,
+            loader,
+            children: OnceCell::new(),
+        }
+    }
+
+    /// The value of this node
+    #[inline]
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    /// The value of this node, mutably. Children that were loaded already
+    /// aren't loaded again.
+    #[inline]
+    pub fn value_mut(&mut self) -> &mut V {
+        &mut self.value
+    }
+
+    /// The keys leading from the root to this node
+    #[inline]
+    pub fn path(&self) -> &[K] {
+        &self.path
+    }
+
+    /// The loader shared by all the nodes of the tree
+    #[inline]
+    pub fn loader(&self) -> &L {
+        &self.loader
+    }
+
+    /// Checks if the children of this node were loaded
+    #[inline]
+    pub fn is_expanded(&self) -> bool {
+        self.children.get().is_some()
+    }
+
+    /// The children of this node if they were loaded, without loading them
+    #[inline]
+    pub fn loaded_children(&self) -> Option<&BTreeMap<K, Self>> {
+        self.children.get()
+    }
+
+    /// Drops the children of this node, so they are loaded again the next
+    /// time they're needed
+    #[inline]
+    pub fn collapse(&mut self) {
+        self.children.take();
+    }
+}
+
+impl<K: Ord + Clone, V, L> LazyTree<K, V, L> {
+    /// Stores the loaded children of this node, unless some were stored
+    /// already, keeping the last value of repeated keys
+    pub(crate) fn expand(&self, children: Vec<(K, V)>) -> &BTreeMap<K, Self> {
+        self.children.get_or_init(|| {
+            children
+                .into_iter()
+                .map(|(key, value)| {
+                    let mut path = self.path.clone();
+                    path.push(key.clone());
+                    (key, Self::with_path(value, path, self.loader.clone()))
+                })
+                .collect()
+        })
+    }
+}
+
+impl<K: Ord + Clone, V, L: Loader<K, V>> LazyTree<K, V, L> {
+    /// The children of this node, loading them if they weren't already
+    pub fn children(&self) -> &BTreeMap<K, Self> {
+        match self.loaded_children() {
+            Some(children) => children,
+            None => {
+                self.expand(self.loader.load_children(&self.path, &self.value))
+            }
+        }
+    }
+
+    fn children_mut(&mut self) -> &mut BTreeMap<K, Self> {
+        self.children();
+        self.children
+            .get_mut()
+            .expect("the children were just loaded")
+    }
+
+    /// The child at `key`, loading the children of this node if they
+    /// weren't already
+    #[inline]
+    pub fn get_child<Q>(&self, key: &Q) -> Option<&Self>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.children().get(key)
+    }
+
+    /// The child at `key`, loading the children of this node if they
+    /// weren't already
+    #[inline]
+    pub fn get_child_mut<Q>(&mut self, key: &Q) -> Option<&mut Self>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.children_mut().get_mut(key)
+    }
+
+    /// Visits the children of this node, loading them if they weren't already
+    #[inline]
+    pub fn iter_single(&self) -> btree_map::Iter<'_, K, Self> {
+        self.children().iter()
+    }
+
+    /// Visits the children of this node mutably, loading them if they
+    /// weren't already
+    #[inline]
+    pub fn iter_single_mut(&mut self) -> btree_map::IterMut<'_, K, Self> {
+        self.children_mut().iter_mut()
+    }
+
+    /// Loads the nodes down to `max_depth` levels below this one, skipping
+    /// the ones that were loaded already
+    pub fn expand_all(&self, max_depth: usize) {
+        let mut stack = vec![(self, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            if depth < max_depth {
+                stack.extend(node.children().values().map(|c| (c, depth + 1)));
+            }
+        }
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug, L> fmt::Debug for LazyTree<K, V, L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyTree")
+            .field("value", &self.value)
+            .field("path", &self.path)
+            .field("children", &self.children.get())
+            .finish()
+    }
+}
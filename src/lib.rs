@@ -1,19 +1,61 @@
-mod iterators;
+#![cfg_attr(not(feature = "std"), no_std)]
 
-pub use iterators::*;
+extern crate alloc;
 
+#[cfg(feature = "std")]
+pub mod binary;
+mod builder;
+#[cfg(feature = "serde")]
+pub mod compact;
+#[cfg(feature = "serde")]
+pub mod de;
+pub mod display;
+pub mod dot;
+#[cfg(feature = "serde")]
+pub mod flat;
+#[cfg(feature = "fs")]
+pub mod fs;
+#[cfg(any(feature = "arbitrary", feature = "quickcheck"))]
+mod fuzz;
+pub mod glob;
+mod impls;
+mod iterators;
+#[cfg(feature = "std")]
+pub mod kv;
+pub mod lazy;
+mod macros;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod path;
+mod radix;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+#[cfg(feature = "stream")]
+pub mod stream;
+mod trie;
+pub mod value;
+
+pub use builder::*;
+pub use iterators::*;
+pub use radix::*;
+pub use trie::*;
 #[cfg(feature = "serde")]
-use serde::{Deserialize, Serialize};
-use std::{
+pub use value::{from_tree, to_tree};
+
+use alloc::collections::btree_map::{
+    self, BTreeMap, IntoKeys, IntoValues, Keys, Range, RangeMut, Values,
+    ValuesMut,
+};
+use core::{
     borrow::Borrow,
-    collections::btree_map::{
-        self, BTreeMap, IntoKeys, IntoValues, Keys, Values, ValuesMut,
-    },
-    ops::Index,
+    mem,
+    ops::{Index, RangeBounds},
 };
+#[cfg(feature = "serde")]
+use serde::Serialize;
 
-#[derive(Debug, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Tree<K: Ord, V> {
     value: V,
     children: BTreeMap<K, Tree<K, V>>,
@@ -39,7 +81,7 @@ impl<K: Ord, V> Tree<K, V> {
     }
     #[inline]
     pub fn set_value(&mut self, mut value: V) -> V {
-        std::mem::swap(&mut self.value, &mut value);
+        mem::swap(&mut self.value, &mut value);
         value
     }
 
@@ -60,34 +102,91 @@ impl<K: Ord, V> Tree<K, V> {
 
     /// An iterator visiting the children without nesting
     #[inline]
-    pub fn iter_single(&self) -> btree_map::Iter<K, Self> {
+    pub fn iter_single(&self) -> btree_map::Iter<'_, K, Self> {
         self.children.iter()
     }
 
     /// An iterator visiting the children without nesting and returning mutable
     /// references
     #[inline]
-    pub fn iter_single_mut(&mut self) -> btree_map::IterMut<K, Self> {
+    pub fn iter_single_mut(&mut self) -> btree_map::IterMut<'_, K, Self> {
         self.children.iter_mut()
     }
 
+    /// An iterator visiting the children whose keys are within the range
+    #[inline]
+    pub fn range_children<Q, R>(&self, range: R) -> Range<'_, K, Self>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        self.children.range(range)
+    }
+
+    /// An iterator visiting the children whose keys are within the range and
+    /// returning mutable references
+    #[inline]
+    pub fn range_children_mut<Q, R>(
+        &mut self,
+        range: R,
+    ) -> RangeMut<'_, K, Self>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        self.children.range_mut(range)
+    }
+
+    /// An iterator visiting the children whose keys start with the prefix
     #[inline]
-    pub fn iter_depth_first(&self) -> DepthFirstIter<K, V> {
+    pub fn children_with_prefix<'a, 'p>(
+        &'a self,
+        prefix: &'p str,
+    ) -> PrefixIter<'a, 'p, K, V>
+    where
+        K: Borrow<str>,
+    {
+        PrefixIter::new(self, prefix)
+    }
+
+    #[inline]
+    pub fn iter_depth_first(&self) -> DepthFirstIter<'_, K, V> {
         DepthFirstIter::new(self)
     }
 
     #[inline]
-    pub fn iter_depth_first_mut(&mut self) -> DepthFirstIterMut<K, V> {
+    pub fn iter_depth_first_mut(&mut self) -> DepthFirstIterMut<'_, K, V> {
         DepthFirstIterMut::new(self)
     }
 
+    /// An iterator visiting the nodes in depth first order along with the
+    /// keys leading to them from this node
+    #[inline]
+    pub fn iter_paths(&self) -> PathIter<'_, K, V> {
+        PathIter::new(self)
+    }
+
+    /// An iterator visiting the values in depth first order along with the
+    /// keys leading to them, skipping the root
+    #[inline]
+    pub fn flat_iter(&self) -> FlatIter<'_, K, V> {
+        self.flat_iter_with(FlatOptions::default())
+    }
+
     #[inline]
-    pub fn iter_breadth_first(&self) -> BreadthFirstIter<K, V> {
+    pub fn flat_iter_with(&self, options: FlatOptions) -> FlatIter<'_, K, V> {
+        FlatIter::new(self, options)
+    }
+
+    #[inline]
+    pub fn iter_breadth_first(&self) -> BreadthFirstIter<'_, K, V> {
         BreadthFirstIter::new(self)
     }
 
     #[inline]
-    pub fn iter_breadth_first_mut(&mut self) -> BreadthFirstIterMut<K, V> {
+    pub fn iter_breadth_first_mut(&mut self) -> BreadthFirstIterMut<'_, K, V> {
         BreadthFirstIterMut::new(self)
     }
 
@@ -119,41 +218,115 @@ impl<K: Ord, V> Tree<K, V> {
     }
 
     #[inline]
-    pub fn get_child<Q: ?Sized>(&self, key: &Q) -> Option<&Self>
+    pub fn get_child<Q>(&self, key: &Q) -> Option<&Self>
     where
         K: Borrow<Q>,
-        Q: Ord,
+        Q: Ord + ?Sized,
     {
         self.children.get(key)
     }
 
     #[inline]
-    pub fn get_key_value<Q: ?Sized>(&self, key: &Q) -> Option<(&K, &Self)>
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &Self)>
     where
         K: Borrow<Q>,
-        Q: Ord,
+        Q: Ord + ?Sized,
     {
         self.children.get_key_value(key)
     }
 
     #[inline]
-    pub fn contains_key<Q: ?Sized>(&self, key: &Q) -> bool
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
     where
         K: Borrow<Q>,
-        Q: Ord,
+        Q: Ord + ?Sized,
     {
         self.children.contains_key(key)
     }
 
     #[inline]
-    pub fn get_child_mut<Q: ?Sized>(&mut self, key: &Q) -> Option<&mut Self>
+    pub fn get_child_mut<Q>(&mut self, key: &Q) -> Option<&mut Self>
     where
         K: Borrow<Q>,
-        Q: Ord,
+        Q: Ord + ?Sized,
     {
         self.children.get_mut(key)
     }
 
+    /// Follows the keys down the tree, the empty path leading to this node
+    #[inline]
+    pub fn subtree_at<'q, Q, I>(&self, path: I) -> Option<&Self>
+    where
+        I: IntoIterator<Item = &'q Q>,
+        K: Borrow<Q>,
+        Q: Ord + ?Sized + 'q,
+    {
+        path.into_iter()
+            .try_fold(self, |node, key| node.get_child(key))
+    }
+
+    #[inline]
+    pub fn subtree_at_mut<'q, Q, I>(&mut self, path: I) -> Option<&mut Self>
+    where
+        I: IntoIterator<Item = &'q Q>,
+        K: Borrow<Q>,
+        Q: Ord + ?Sized + 'q,
+    {
+        path.into_iter()
+            .try_fold(self, |node, key| node.get_child_mut(key))
+    }
+
+    /// Gets the value of the node at the end of the path
+    #[inline]
+    pub fn value_at<'q, Q, I>(&self, path: I) -> Option<&V>
+    where
+        I: IntoIterator<Item = &'q Q>,
+        K: Borrow<Q>,
+        Q: Ord + ?Sized + 'q,
+    {
+        self.subtree_at(path).map(Tree::value)
+    }
+
+    #[inline]
+    pub fn value_at_mut<'q, Q, I>(&mut self, path: I) -> Option<&mut V>
+    where
+        I: IntoIterator<Item = &'q Q>,
+        K: Borrow<Q>,
+        Q: Ord + ?Sized + 'q,
+    {
+        self.subtree_at_mut(path).map(Tree::value_mut)
+    }
+
+    #[inline]
+    pub fn contains_path<'q, Q, I>(&self, path: I) -> bool
+    where
+        I: IntoIterator<Item = &'q Q>,
+        K: Borrow<Q>,
+        Q: Ord + ?Sized + 'q,
+    {
+        self.subtree_at(path).is_some()
+    }
+
+    #[inline]
+    pub fn first_child(&self) -> Option<(&K, &Self)> {
+        self.children.first_key_value()
+    }
+
+    #[inline]
+    pub fn last_child(&self) -> Option<(&K, &Self)> {
+        self.children.last_key_value()
+    }
+
+    #[inline]
+    pub fn pop_first_child(&mut self) -> Option<(K, Self)> {
+        self.children.pop_first()
+    }
+
+    #[inline]
+    pub fn pop_last_child(&mut self) -> Option<(K, Self)> {
+        self.children.pop_last()
+    }
+
     #[inline]
     pub fn add_child(
         &mut self,
@@ -163,7 +336,7 @@ impl<K: Ord, V> Tree<K, V> {
         match self.entry(key) {
             Entry::Occupied(entry) => {
                 let child = entry.into_mut();
-                std::mem::swap(&mut value, &mut child.value);
+                mem::swap(&mut value, &mut child.value);
                 (Some(value), child)
             }
             Entry::Vacant(entry) => (None, entry.insert(value)),
@@ -171,19 +344,19 @@ impl<K: Ord, V> Tree<K, V> {
     }
 
     #[inline]
-    pub fn remove<Q: ?Sized>(&mut self, key: &Q) -> Option<Self>
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<Self>
     where
         K: Borrow<Q>,
-        Q: Ord,
+        Q: Ord + ?Sized,
     {
         self.children.remove(key)
     }
 
     #[inline]
-    pub fn remove_entry<Q: ?Sized>(&mut self, key: &Q) -> Option<(K, Self)>
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, Self)>
     where
         K: Borrow<Q>,
-        Q: Ord,
+        Q: Ord + ?Sized,
     {
         self.children.remove_entry(key)
     }
@@ -197,13 +370,31 @@ impl<K: Ord, V> Tree<K, V> {
     }
 
     #[inline]
-    pub fn into_keys(self) -> IntoKeys<K, Self> {
-        self.children.into_keys()
+    pub fn into_keys(mut self) -> IntoKeys<K, Self> {
+        mem::take(&mut self.children).into_keys()
+    }
+
+    #[inline]
+    pub fn into_values(mut self) -> IntoValues<K, Self> {
+        mem::take(&mut self.children).into_values()
     }
 
+    /// Turns the tree into its values in depth first order along with the
+    /// keys leading to them, skipping the root
     #[inline]
-    pub fn into_values(self) -> IntoValues<K, Self> {
-        self.children.into_values()
+    pub fn into_flat(self) -> IntoFlat<K, V>
+    where
+        K: Clone,
+    {
+        self.into_flat_with(FlatOptions::default())
+    }
+
+    #[inline]
+    pub fn into_flat_with(self, options: FlatOptions) -> IntoFlat<K, V>
+    where
+        K: Clone,
+    {
+        IntoFlat::new(self, options)
     }
 }
 
@@ -224,8 +415,8 @@ impl<K: Ord, V> IntoIterator for Tree<K, V> {
     type Item = (K, Tree<K, V>);
     type IntoIter = btree_map::IntoIter<K, Tree<K, V>>;
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.children.into_iter()
+    fn into_iter(mut self) -> Self::IntoIter {
+        mem::take(&mut self.children).into_iter()
     }
 }
 
@@ -334,7 +525,7 @@ impl<'a, K: Ord, V> OccupiedEntry<'a, K, V> {
     #[inline]
     pub fn insert(&mut self, mut value: V) -> (V, &mut Tree<K, V>) {
         let child = self.get_mut();
-        std::mem::swap(&mut value, &mut child.value);
+        mem::swap(&mut value, &mut child.value);
         (value, child)
     }
 
@@ -0,0 +1,66 @@
+/// Builds a [`Tree`](crate::Tree) from its root value followed by its
+/// children, each written as `key => value`, optionally followed by its own
+/// children in braces
+///
+/// ```
+/// use tagged_tree::tree;
+///
+/// let root_value = 0;
+/// let tree = tree!{ root_value; "a" => 1 { "b" => 2, "c" => 3 }, "d" => 4 };
+/// assert_eq!(*tree.value(), 0);
+/// assert_eq!(*tree["a"]["c"].value(), 3);
+/// assert!(tree["d"].is_childless());
+/// ```
+///
+/// Keys can be any expressions. Values are single tokens, so anything longer
+/// than a literal or a variable has to be wrapped in parentheses or braces:
+///
+/// ```
+/// let offset = 10;
+/// let tree = tagged_tree::tree! { 0;
+///     "a" => (offset * 2) { "b" => { offset + 1 } },
+/// };
+/// assert_eq!(*tree["a"]["b"].value(), 11);
+/// ```
+///
+/// The children of a node are added in a single expansion, so there can be
+/// any number of them, but each level of nesting counts towards the
+/// recursion limit. When a key is repeated, the later value wins and the
+/// children are merged.
+///
+/// Malformed input is rejected at compile time:
+///
+/// ```compile_fail
+/// let tree = tagged_tree::tree! { 0; "a" 1 };
+/// ```
+///
+/// ```compile_fail
+/// let tree = tagged_tree::tree! { 0; "a" => 1 + 1 };
+/// ```
+///
+/// ```compile_fail
+/// let tree = tagged_tree::tree! { 0; "a" => 1 { "b" => 2 } "c" => 3 };
+/// ```
+#[macro_export]
+macro_rules! tree {
+    (@children $tree:ident;
+        $($key:expr => $value:tt $({ $($children:tt)* })?),* $(,)?
+    ) => {
+        $({
+            let (_, _child) = $tree.add_child($key, $value);
+            $($crate::tree!(@children _child; $($children)*);)?
+        })*
+    };
+    (@children $tree:ident; $($rest:tt)+) => {
+        compile_error!("expected `key => value`")
+    };
+
+    ($value:expr $(;)?) => {
+        $crate::Tree::new($value)
+    };
+    ($value:expr; $($children:tt)+) => {{
+        let mut tree = $crate::Tree::new($value);
+        $crate::tree!(@children tree; $($children)+);
+        tree
+    }};
+}
@@ -0,0 +1,744 @@
+//! A tree stored on disk in the [`binary`](crate::binary) format, read
+//! through a memory map so that only the parts being used are loaded.
+//!
+//! Changes are kept in memory, copying only the nodes being modified, until
+//! they are saved. Saving copies the untouched subtrees from the old file as
+//! is.
+//!
+//! ```
+//! use tagged_tree::{mmap::MappedTree, Tree};
+//!
+//! # let dir = tempfile::tempdir().unwrap();
+//! # let path = dir.path().join("tree.bin");
+//! let mut tree = Tree::new(0u32);
+//! tree.entry("a".to_owned()).or_insert(1).add_child("b".to_owned(), 2);
+//! std::fs::write(&path, tree.to_binary().unwrap()).unwrap();
+//!
+//! let mut mapped = MappedTree::<String, u32>::open(&path).unwrap();
+//! assert_eq!(*mapped.root().get_child(&"a".to_owned()).unwrap().value(), 1);
+//!
+//! mapped.set_value(&["a".to_owned()], 10);
+//! mapped.save().unwrap();
+//! assert_eq!(*mapped.root().get_child(&"a".to_owned()).unwrap().value(), 10);
+//! ```
+
+use crate::{
+    binary::{self, ArchivedTree, Decode, Encode, Output, Parts, Source},
+    Entry, Tree,
+};
+use memmap2::Mmap;
+use std::{
+    collections::{btree_map, BTreeMap},
+    ffi::OsString,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, Seek, SeekFrom, Write},
+    mem,
+    ops::Deref,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// How much is buffered before writing to the file when saving
+const BUFFER_SIZE: usize = 1 << 16;
+
+/// A tree in a memory mapped file
+pub struct MappedTree<K: Ord, V> {
+    path: PathBuf,
+    map: Mmap,
+    root: Change<K, V>,
+}
+
+/// How a node differs from the one in the file
+enum Change<K: Ord, V> {
+    Edit(Delta<K, V>),
+    Replace(Tree<K, V>),
+    Remove,
+}
+
+/// The changes made to a node that is still in the file
+struct Delta<K: Ord, V> {
+    value: Option<V>,
+    children: BTreeMap<K, Change<K, V>>,
+}
+
+impl<K: Ord, V> Delta<K, V> {
+    #[inline]
+    fn new() -> Self {
+        Delta {
+            value: None,
+            children: BTreeMap::new(),
+        }
+    }
+}
+
+/// A node that can be modified
+enum Target<'m, K: Ord, V> {
+    Mapped(ArchivedTree<'m, K, V>, &'m mut Delta<K, V>),
+    Owned(&'m mut Tree<K, V>),
+}
+
+impl<K, V> MappedTree<K, V>
+where
+    K: Ord + Clone + Encode + for<'a> Decode<'a>,
+    V: Encode + for<'a> Decode<'a>,
+{
+    /// Opens a file written with [`Tree::to_binary`], checking all of it
+    ///
+    /// The file must not be modified by anything else while it is open.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let tree = Self::open_trusted(path)?;
+        ArchivedTree::<K, V>::new(&tree.map)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Ok(tree)
+    }
+
+    /// Opens a file without checking it first, so that only the parts being
+    /// used are read. Reading a malformed file panics.
+    ///
+    /// The file must not be modified by anything else while it is open.
+    pub fn open_trusted(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_owned();
+        let map = map(&path)?;
+        ArchivedTree::<K, V>::trusted(&map)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Ok(MappedTree {
+            path,
+            map,
+            root: Change::Edit(Delta::new()),
+        })
+    }
+
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    #[inline]
+    pub fn root(&self) -> Node<'_, K, V> {
+        match &self.root {
+            Change::Edit(delta) => Node::mapped(self.archived(), Some(delta)),
+            Change::Replace(tree) => Node::owned(tree),
+            Change::Remove => unreachable!("the root can't be removed"),
+        }
+    }
+
+    /// Follows the keys down the tree
+    #[inline]
+    pub fn get_path<'k, I>(&self, path: I) -> Option<Node<'_, K, V>>
+    where
+        I: IntoIterator<Item = &'k K>,
+        K: 'k,
+    {
+        self.root().get_path(path)
+    }
+
+    /// Whether there are changes that haven't been saved
+    pub fn is_modified(&self) -> bool {
+        match &self.root {
+            Change::Edit(delta) => {
+                delta.value.is_some() || !delta.children.is_empty()
+            }
+            _ => true,
+        }
+    }
+
+    /// Drops the changes that haven't been saved
+    #[inline]
+    pub fn discard_changes(&mut self) {
+        self.root = Change::Edit(Delta::new());
+    }
+
+    /// Replaces the value of a node, returning the old one, or `None` if
+    /// there's no node at the path
+    pub fn set_value(&mut self, path: &[K], value: V) -> Option<V> {
+        match self.target(path)? {
+            Target::Mapped(archived, delta) => Some(
+                delta
+                    .value
+                    .replace(value)
+                    .unwrap_or_else(|| archived.value()),
+            ),
+            Target::Owned(tree) => Some(tree.set_value(value)),
+        }
+    }
+
+    /// Inserts a subtree at the path, replacing anything already there.
+    /// Returns `false` if the parent of the path doesn't exist.
+    pub fn insert(&mut self, path: &[K], tree: Tree<K, V>) -> bool {
+        let (key, parent) = match path.split_last() {
+            Some(split) => split,
+            None => {
+                self.root = Change::Replace(tree);
+                return true;
+            }
+        };
+        match self.target(parent) {
+            Some(Target::Mapped(_, delta)) => {
+                delta.children.insert(key.clone(), Change::Replace(tree));
+            }
+            Some(Target::Owned(parent)) => match parent.entry(key.clone()) {
+                Entry::Occupied(mut entry) => {
+                    entry.insert_tree(tree);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert_tree(tree);
+                }
+            },
+            None => return false,
+        }
+        true
+    }
+
+    /// Removes the subtree at the path, returning whether there was one. The
+    /// root can't be removed.
+    pub fn remove(&mut self, path: &[K]) -> bool {
+        let (key, parent) = match path.split_last() {
+            Some(split) => split,
+            None => return false,
+        };
+        if self.get_path(path).is_none() {
+            return false;
+        }
+        match self.target(parent) {
+            Some(Target::Mapped(archived, delta)) => {
+                let mapped = archived.get_child(key).is_some();
+                match delta.children.entry(key.clone()) {
+                    btree_map::Entry::Occupied(mut entry) => {
+                        if let Change::Remove = entry.get() {
+                            return false;
+                        }
+                        if mapped {
+                            entry.insert(Change::Remove);
+                        } else {
+                            entry.remove();
+                        }
+                        true
+                    }
+                    btree_map::Entry::Vacant(entry) => {
+                        if mapped {
+                            entry.insert(Change::Remove);
+                        }
+                        mapped
+                    }
+                }
+            }
+            Some(Target::Owned(parent)) => parent.remove(key).is_some(),
+            None => false,
+        }
+    }
+
+    /// Saves the changes, replacing the file
+    ///
+    /// If the file can't be replaced, the changes are kept. If the old file
+    /// can't be mapped again either, the tree is read from the new file,
+    /// left next to it, instead.
+    pub fn save(&mut self) -> io::Result<()> {
+        let (temp, file) = temp_file(&self.path)?;
+        let mut out = FileOutput {
+            file,
+            buffer: Vec::with_capacity(BUFFER_SIZE),
+            offset: 0,
+        };
+        let written = binary::encode_into(self.root(), &mut out)
+            .and_then(|()| out.finish());
+        if let Err(err) = written {
+            let _ = fs::remove_file(&temp);
+            return Err(err);
+        }
+
+        let new = match map(&temp) {
+            Ok(new) => new,
+            Err(err) => {
+                let _ = fs::remove_file(&temp);
+                return Err(err);
+            }
+        };
+
+        // Windows can't replace a file that is still mapped, so the tree
+        // moves to the new file before it takes the place of the old one
+        let old = mem::replace(&mut self.map, new);
+        let changes = mem::replace(&mut self.root, Change::Edit(Delta::new()));
+        drop(old);
+        if let Err(err) = fs::rename(&temp, &self.path) {
+            // go back to the old file, which wasn't replaced, or stay on the
+            // new one if it can't be mapped again
+            if let Ok(old) = map(&self.path) {
+                self.map = old;
+                self.root = changes;
+                let _ = fs::remove_file(&temp);
+            }
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Encodes the tree, changes included, in the binary format
+    #[inline]
+    pub fn to_binary(&self) -> io::Result<Vec<u8>> {
+        binary::encode(self.root())
+    }
+
+    #[inline]
+    fn archived(&self) -> ArchivedTree<'_, K, V> {
+        ArchivedTree::trusted(&self.map).expect("checked when opened")
+    }
+
+    /// Finds a node to modify, recording edits for the nodes on the way
+    fn target(&mut self, path: &[K]) -> Option<Target<'_, K, V>> {
+        // nothing is recorded unless the whole path is there
+        self.get_path(path)?;
+        let mut archived =
+            ArchivedTree::trusted(&self.map).expect("checked when opened");
+        let mut change = &mut self.root;
+        for (index, key) in path.iter().enumerate() {
+            let delta = match change {
+                Change::Edit(delta) => delta,
+                Change::Replace(tree) => {
+                    return path[index..]
+                        .iter()
+                        .try_fold(tree, |tree, key| tree.get_child_mut(key))
+                        .map(Target::Owned);
+                }
+                Change::Remove => return None,
+            };
+
+            let child = archived.get_child(key);
+            change = match delta.children.entry(key.clone()) {
+                btree_map::Entry::Occupied(entry) => entry.into_mut(),
+                btree_map::Entry::Vacant(entry) => {
+                    child?;
+                    entry.insert(Change::Edit(Delta::new()))
+                }
+            };
+            if let Some(child) = child {
+                archived = child;
+            }
+        }
+
+        match change {
+            Change::Edit(delta) => Some(Target::Mapped(archived, delta)),
+            Change::Replace(tree) => Some(Target::Owned(tree)),
+            Change::Remove => None,
+        }
+    }
+}
+
+impl<K, V> MappedTree<K, V>
+where
+    K: Ord + Clone + Encode + for<'a> Decode<'a>,
+    V: Clone + Encode + for<'a> Decode<'a>,
+{
+    /// Copies the subtree at the path into memory to modify it
+    pub fn get_mut(&mut self, path: &[K]) -> Option<&mut Tree<K, V>> {
+        let (key, parent) = match path.split_last() {
+            Some(split) => split,
+            None => {
+                if let Change::Edit(_) = self.root {
+                    self.root = Change::Replace(self.root().to_tree());
+                }
+                return match &mut self.root {
+                    Change::Replace(tree) => Some(tree),
+                    _ => unreachable!("the root has just been replaced"),
+                };
+            }
+        };
+        self.get_path(path)?;
+
+        let delta = match self.target(parent)? {
+            Target::Mapped(archived, delta) => {
+                let tree = match delta.children.get(key) {
+                    Some(Change::Edit(edit)) => {
+                        let child = archived
+                            .get_child(key)
+                            .expect("edited nodes are in the file");
+                        Some(Node::mapped(child, Some(edit)).to_tree())
+                    }
+                    Some(Change::Replace(_)) => None,
+                    Some(Change::Remove) => return None,
+                    None => Some(
+                        Node::mapped(archived.get_child(key)?, None).to_tree(),
+                    ),
+                };
+                if let Some(tree) = tree {
+                    delta.children.insert(key.clone(), Change::Replace(tree));
+                }
+                delta
+            }
+            Target::Owned(parent) => return parent.get_child_mut(key),
+        };
+        match delta.children.get_mut(key) {
+            Some(Change::Replace(tree)) => Some(tree),
+            _ => unreachable!("the child has been replaced"),
+        }
+    }
+
+    /// Reads the whole tree into memory, changes included
+    #[inline]
+    pub fn to_tree(&self) -> Tree<K, V> {
+        self.root().to_tree()
+    }
+}
+
+impl<K: Ord, V> fmt::Debug for MappedTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MappedTree")
+            .field("path", &self.path)
+            .field("len", &self.map.len())
+            .finish()
+    }
+}
+
+/// Creates a file next to `path` to write it anew, with a name no other save
+/// is using
+fn temp_file(path: &Path) -> io::Result<(PathBuf, File)> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    loop {
+        let mut name = OsString::from(path);
+        name.push(format!(
+            ".{}-{}.tmp",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let temp = PathBuf::from(name);
+        match OpenOptions::new().write(true).create_new(true).open(&temp) {
+            Ok(file) => return Ok((temp, file)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn map(path: &Path) -> io::Result<Mmap> {
+    let file = File::open(path)?;
+    // SAFETY: the file isn't modified while it is mapped, as documented on
+    // `MappedTree::open`
+    unsafe { Mmap::map(&file) }
+}
+
+/// A node of a [`MappedTree`], either read from the file or modified in
+/// memory
+pub struct Node<'m, K: Ord, V> {
+    inner: Inner<'m, K, V>,
+}
+
+enum Inner<'m, K: Ord, V> {
+    Mapped(ArchivedTree<'m, K, V>, Option<&'m Delta<K, V>>),
+    Owned(&'m Tree<K, V>),
+}
+
+impl<'m, K: Ord, V> Node<'m, K, V> {
+    #[inline]
+    fn mapped(
+        archived: ArchivedTree<'m, K, V>,
+        delta: Option<&'m Delta<K, V>>,
+    ) -> Self {
+        Node {
+            inner: Inner::Mapped(archived, delta),
+        }
+    }
+
+    #[inline]
+    fn owned(tree: &'m Tree<K, V>) -> Self {
+        Node {
+            inner: Inner::Owned(tree),
+        }
+    }
+}
+
+/// The value of a [`Node`], either borrowed or decoded from the file
+#[derive(Debug, Clone)]
+pub enum NodeValue<'m, V> {
+    Borrowed(&'m V),
+    Decoded(V),
+}
+
+impl<V: Clone> NodeValue<'_, V> {
+    #[inline]
+    pub fn into_owned(self) -> V {
+        match self {
+            NodeValue::Borrowed(value) => value.clone(),
+            NodeValue::Decoded(value) => value,
+        }
+    }
+}
+
+impl<V> Deref for NodeValue<'_, V> {
+    type Target = V;
+
+    #[inline]
+    fn deref(&self) -> &V {
+        match self {
+            NodeValue::Borrowed(value) => value,
+            NodeValue::Decoded(value) => value,
+        }
+    }
+}
+
+impl<'m, K, V> Node<'m, K, V>
+where
+    K: Ord + Clone + Encode + Decode<'m>,
+    V: Decode<'m>,
+{
+    #[inline]
+    pub fn value(&self) -> NodeValue<'m, V> {
+        match self.inner {
+            Inner::Mapped(archived, delta) => {
+                match delta.and_then(|delta| delta.value.as_ref()) {
+                    Some(value) => NodeValue::Borrowed(value),
+                    None => NodeValue::Decoded(archived.value()),
+                }
+            }
+            Inner::Owned(tree) => NodeValue::Borrowed(tree.value()),
+        }
+    }
+
+    pub fn get_child(&self, key: &K) -> Option<Self> {
+        match self.inner {
+            Inner::Mapped(archived, None) => archived
+                .get_child(key)
+                .map(|child| Node::mapped(child, None)),
+            Inner::Mapped(archived, Some(delta)) => {
+                match delta.children.get(key) {
+                    Some(Change::Edit(edit)) => {
+                        Some(Node::mapped(archived.get_child(key)?, Some(edit)))
+                    }
+                    Some(Change::Replace(tree)) => Some(Node::owned(tree)),
+                    Some(Change::Remove) => None,
+                    None => archived
+                        .get_child(key)
+                        .map(|child| Node::mapped(child, None)),
+                }
+            }
+            Inner::Owned(tree) => tree.get_child(key).map(Node::owned),
+        }
+    }
+
+    #[inline]
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get_child(key).is_some()
+    }
+
+    /// Follows the keys down the tree
+    pub fn get_path<'k, I>(&self, path: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'k K>,
+        K: 'k,
+    {
+        let mut node = self.clone();
+        for key in path {
+            node = node.get_child(key)?;
+        }
+        Some(node)
+    }
+
+    /// The children with their keys, in key order
+    pub fn iter_single(&self) -> std::vec::IntoIter<(K, Self)> {
+        let mut children: Vec<_> = match self.inner {
+            Inner::Mapped(archived, None) => archived
+                .children()
+                .map(|(key, child)| (key, Node::mapped(child, None)))
+                .collect(),
+            Inner::Mapped(archived, Some(delta)) => {
+                let mut children: Vec<_> = archived
+                    .children()
+                    .filter(|(key, _)| !delta.children.contains_key(key))
+                    .map(|(key, child)| (key, Node::mapped(child, None)))
+                    .collect();
+                for (key, change) in &delta.children {
+                    let node = match change {
+                        Change::Edit(edit) => Node::mapped(
+                            archived
+                                .get_child(key)
+                                .expect("edited nodes are in the file"),
+                            Some(edit),
+                        ),
+                        Change::Replace(tree) => Node::owned(tree),
+                        Change::Remove => continue,
+                    };
+                    children.push((key.clone(), node));
+                }
+                children
+            }
+            Inner::Owned(tree) => tree
+                .iter_single()
+                .map(|(key, child)| (key.clone(), Node::owned(child)))
+                .collect(),
+        };
+        children.sort_by(|(left, _), (right, _)| left.cmp(right));
+        children.into_iter()
+    }
+
+    #[inline]
+    pub fn children_keys(&self) -> Vec<K> {
+        self.iter_single().map(|(key, _)| key).collect()
+    }
+
+    #[inline]
+    pub fn children(&self) -> impl Iterator<Item = Self> {
+        self.iter_single().map(|(_, child)| child)
+    }
+
+    pub fn child_count(&self) -> usize {
+        match self.inner {
+            Inner::Mapped(archived, None) => archived.child_count(),
+            Inner::Owned(tree) => tree.children().len(),
+            Inner::Mapped(_, Some(_)) => self.iter_single().len(),
+        }
+    }
+
+    #[inline]
+    pub fn is_childless(&self) -> bool {
+        self.child_count() == 0
+    }
+
+    /// Reads the subtree into memory
+    pub fn to_tree(&self) -> Tree<K, V>
+    where
+        V: Clone,
+    {
+        type Frame<'m, K, V> = (
+            Option<K>,
+            Tree<K, V>,
+            std::vec::IntoIter<(K, Node<'m, K, V>)>,
+        );
+
+        let mut stack: Vec<Frame<'m, K, V>> = vec![(
+            None,
+            Tree::new(self.value().into_owned()),
+            self.iter_single(),
+        )];
+        loop {
+            let (_, _, children) = stack.last_mut().expect("the root is last");
+            if let Some((key, child)) = children.next() {
+                stack.push((
+                    Some(key),
+                    Tree::new(child.value().into_owned()),
+                    child.iter_single(),
+                ));
+                continue;
+            }
+
+            let (key, tree, _) = stack.pop().expect("the root is last");
+            match (key, stack.last_mut()) {
+                (Some(key), Some((_, parent, _))) => {
+                    parent.children.insert(key, tree);
+                }
+                _ => return tree,
+            }
+        }
+    }
+}
+
+impl<'m, K: Ord, V> Source<'m> for Node<'m, K, V>
+where
+    K: Clone + Encode + Decode<'m>,
+    V: Encode + Decode<'m>,
+{
+    fn parts(self) -> Parts<'m, Self> {
+        let delta = match self.inner {
+            Inner::Mapped(archived, None) => {
+                return Parts::Encoded(archived.as_bytes())
+            }
+            Inner::Mapped(archived, Some(delta)) => (archived, delta),
+            Inner::Owned(tree) => {
+                return match tree.parts() {
+                    Parts::Node { value, children } => Parts::Node {
+                        value,
+                        children: children
+                            .into_iter()
+                            .map(|(key, child)| (key, Node::owned(child)))
+                            .collect(),
+                    },
+                    Parts::Encoded(bytes) => Parts::Encoded(bytes),
+                }
+            }
+        };
+
+        let value = match &delta.1.value {
+            Some(value) => {
+                let mut bytes = Vec::new();
+                value.encode(&mut bytes);
+                bytes
+            }
+            None => delta.0.value_bytes().to_vec(),
+        };
+        let children = self
+            .iter_single()
+            .map(|(key, child)| {
+                let mut bytes = Vec::new();
+                key.encode(&mut bytes);
+                (bytes, child)
+            })
+            .collect();
+        Parts::Node { value, children }
+    }
+}
+
+impl<K: Ord, V> Clone for Node<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        match self.inner {
+            Inner::Mapped(archived, delta) => Node::mapped(archived, delta),
+            Inner::Owned(tree) => Node::owned(tree),
+        }
+    }
+}
+
+impl<K: Ord, V> fmt::Debug for Node<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.inner {
+            Inner::Mapped(archived, delta) => f
+                .debug_struct("Mapped")
+                .field("len", &archived.as_bytes().len())
+                .field("modified", &delta.is_some())
+                .finish(),
+            Inner::Owned(_) => f.debug_struct("Owned").finish(),
+        }
+    }
+}
+
+/// Buffers the encoded tree, seeking back to patch what was already written
+struct FileOutput {
+    file: File,
+    buffer: Vec<u8>,
+    offset: usize,
+}
+
+impl FileOutput {
+    fn finish(mut self) -> io::Result<()> {
+        self.file.write_all(&self.buffer)?;
+        self.file.sync_all()
+    }
+}
+
+impl Output for FileOutput {
+    #[inline]
+    fn position(&self) -> usize {
+        self.offset + self.buffer.len()
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.buffer.extend_from_slice(bytes);
+        if self.buffer.len() >= BUFFER_SIZE {
+            self.file.write_all(&self.buffer)?;
+            self.offset += self.buffer.len();
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+
+    fn patch(&mut self, at: usize, bytes: [u8; 4]) -> io::Result<()> {
+        if at >= self.offset {
+            let at = at - self.offset;
+            self.buffer[at..at + 4].copy_from_slice(&bytes);
+            return Ok(());
+        }
+        self.file.seek(SeekFrom::Start(at as u64))?;
+        self.file.write_all(&bytes)?;
+        self.file.seek(SeekFrom::End(0))?;
+        Ok(())
+    }
+}
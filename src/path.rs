@@ -0,0 +1,97 @@
+//! Relations between the nodes of a tree, worked out from the keys leading
+//! to them.
+//!
+//! A path is the sequence of keys from the root to a node, the root's being
+//! empty. Combined with [`Tree::subtree_at`](crate::Tree::subtree_at), these
+//! find related nodes without walking the tree.
+//!
+//! ```
+//! use tagged_tree::path::{common_prefix, is_ancestor, relative_path};
+//!
+//! let a = ["usr", "share", "doc"];
+//! let b = ["usr", "lib"];
+//! assert_eq!(common_prefix(&a, &b), ["usr"]);
+//! assert!(is_ancestor(&["usr"], &a));
+//!
+//! let relative = relative_path(&a, &b);
+//! assert_eq!(relative.up(), 2);
+//! assert_eq!(relative.down(), ["lib"]);
+//! ```
+
+/// The longest path leading to both nodes, which is the path of their lowest
+/// common ancestor
+#[inline]
+pub fn common_prefix<'a, K: PartialEq>(a: &'a [K], b: &[K]) -> &'a [K] {
+    let len = a.iter().zip(b).take_while(|(a, b)| a == b).count();
+    &a[..len]
+}
+
+/// The path of the lowest common ancestor of all the nodes, or `None` when
+/// there are no nodes
+///
+/// A node counts as its own ancestor here, so the lowest common ancestor of a
+/// single node is the node itself.
+pub fn lca<'a, K, P, I>(paths: I) -> Option<&'a [K]>
+where
+    K: PartialEq + 'a,
+    P: AsRef<[K]> + ?Sized + 'a,
+    I: IntoIterator<Item = &'a P>,
+{
+    let mut paths = paths.into_iter();
+    let first = paths.next()?.as_ref();
+    Some(paths.fold(first, |lca, path| common_prefix(lca, path.as_ref())))
+}
+
+/// Checks if the node at `path` is above the node at `of`, a node not being
+/// its own ancestor
+#[inline]
+pub fn is_ancestor<K: PartialEq>(path: &[K], of: &[K]) -> bool {
+    path.len() < of.len() && of.starts_with(path)
+}
+
+/// How to get from the node at `from` to the node at `to`
+#[inline]
+pub fn relative_path<'a, K: PartialEq>(
+    from: &[K],
+    to: &'a [K],
+) -> RelativePath<'a, K> {
+    let common = common_prefix(to, from).len();
+    RelativePath {
+        up: from.len() - common,
+        down: &to[common..],
+    }
+}
+
+/// The way between two nodes, going up some levels to their lowest common
+/// ancestor and then down along some keys
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct RelativePath<'a, K> {
+    up: usize,
+    down: &'a [K],
+}
+
+impl<'a, K> RelativePath<'a, K> {
+    /// The number of levels to go up
+    #[inline]
+    pub fn up(&self) -> usize {
+        self.up
+    }
+
+    /// The keys to follow down from there
+    #[inline]
+    pub fn down(&self) -> &'a [K] {
+        self.down
+    }
+
+    /// The number of edges between the nodes
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.up + self.down.len()
+    }
+
+    /// Checks if both ends are the same node
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
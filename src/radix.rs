@@ -0,0 +1,618 @@
+use crate::{impls::take_parts, Tree};
+use alloc::{
+    collections::{btree_map, BTreeMap},
+    vec,
+    vec::Vec,
+};
+use core::{
+    borrow::Borrow,
+    fmt,
+    hash::{Hash, Hasher},
+    mem,
+};
+
+/// A path compressed variant of [`Tree`].
+///
+/// Chains of nodes that only have a single child are stored as one edge
+/// labelled with all the keys along the chain, so they only need a single
+/// `BTreeMap`. Edges are split and merged as nodes are inserted and removed.
+#[derive(Default)]
+pub struct RadixTree<K: Ord, V> {
+    value: V,
+    children: BTreeMap<K, Edge<K, V>>,
+}
+
+/// A chain of nodes, the first key of the chain is the key in the parent's
+/// map. `values` holds the value of every node along the chain, so it's always
+/// one longer than `tail`.
+struct Edge<K: Ord, V> {
+    tail: Vec<K>,
+    values: Vec<V>,
+    children: BTreeMap<K, Edge<K, V>>,
+}
+
+impl<K: Ord, V> RadixTree<K, V> {
+    #[inline]
+    pub fn new(value: V) -> Self {
+        RadixTree {
+            value,
+            children: BTreeMap::new(),
+        }
+    }
+
+    #[inline]
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    #[inline]
+    pub fn value_mut(&mut self) -> &mut V {
+        &mut self.value
+    }
+
+    #[inline]
+    pub fn set_value(&mut self, value: V) -> V {
+        core::mem::replace(&mut self.value, value)
+    }
+
+    #[inline]
+    pub fn is_childless(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Moves the value and children out without running `Drop`
+    fn into_parts(self) -> (V, BTreeMap<K, Edge<K, V>>) {
+        // SAFETY: the closure returns the fields of the tree
+        unsafe {
+            take_parts(self, |tree| (&mut tree.value, &mut tree.children))
+        }
+    }
+
+    /// The number of edges in the tree, each one standing for a chain of nodes
+    pub fn edge_count(&self) -> usize {
+        let mut count = 0;
+        let mut stack: Vec<&BTreeMap<K, Edge<K, V>>> = vec![&self.children];
+        while let Some(children) = stack.pop() {
+            count += children.len();
+            stack.extend(children.values().map(|edge| &edge.children));
+        }
+        count
+    }
+
+    /// Gets the value of the node at the end of the path, like
+    /// [`Tree::value_at`]
+    pub fn value_at<I>(&self, path: I) -> Option<&V>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<K>,
+    {
+        let mut path = path.into_iter();
+        let mut edge = match path.next() {
+            None => return Some(&self.value),
+            Some(key) => self.children.get(key.borrow())?,
+        };
+        let mut index = 0;
+        for key in path {
+            match edge.tail.get(index) {
+                Some(tail) if tail == key.borrow() => index += 1,
+                Some(_) => return None,
+                None => {
+                    edge = edge.children.get(key.borrow())?;
+                    index = 0;
+                }
+            }
+        }
+        Some(&edge.values[index])
+    }
+
+    /// Gets a mutable reference to the value of the node at the end of the
+    /// path, like [`Tree::value_at_mut`]
+    pub fn value_at_mut<I>(&mut self, path: I) -> Option<&mut V>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<K>,
+    {
+        let mut path = path.into_iter();
+        let mut edge = match path.next() {
+            None => return Some(&mut self.value),
+            Some(key) => self.children.get_mut(key.borrow())?,
+        };
+        let mut index = 0;
+        for key in path {
+            match edge.tail.get(index) {
+                Some(tail) if tail == key.borrow() => index += 1,
+                Some(_) => return None,
+                None => {
+                    edge = edge.children.get_mut(key.borrow())?;
+                    index = 0;
+                }
+            }
+        }
+        Some(&mut edge.values[index])
+    }
+
+    #[inline]
+    pub fn contains_path<I>(&self, path: I) -> bool
+    where
+        I: IntoIterator,
+        I::Item: Borrow<K>,
+    {
+        self.value_at(path).is_some()
+    }
+
+    /// Sets the value of the node at the end of the path, returning the
+    /// previous value if the node already existed. Missing nodes leading up to
+    /// it are created with default values.
+    pub fn insert<I>(&mut self, path: I, value: V) -> Option<V>
+    where
+        I: IntoIterator<Item = K>,
+        V: Default,
+    {
+        let mut path = path.into_iter();
+        let key = match path.next() {
+            None => return Some(self.set_value(value)),
+            Some(key) => key,
+        };
+        let mut edge = match self.children.entry(key) {
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(Edge::chain(path, value));
+                return None;
+            }
+            btree_map::Entry::Occupied(entry) => entry.into_mut(),
+        };
+
+        let mut index = 0;
+        while let Some(key) = path.next() {
+            match edge.tail.get(index) {
+                Some(tail) if *tail == key => index += 1,
+                Some(_) => {
+                    edge.split(index);
+                    edge.children.insert(key, Edge::chain(path, value));
+                    return None;
+                }
+                None if edge.children.is_empty() => {
+                    // extend the chain rather than adding a single child
+                    for key in core::iter::once(key).chain(path) {
+                        edge.tail.push(key);
+                        edge.values.push(V::default());
+                    }
+                    *edge.values.last_mut().expect("chain can't be empty") =
+                        value;
+                    return None;
+                }
+                None => {
+                    edge = match edge.children.entry(key) {
+                        btree_map::Entry::Vacant(entry) => {
+                            entry.insert(Edge::chain(path, value));
+                            return None;
+                        }
+                        btree_map::Entry::Occupied(entry) => entry.into_mut(),
+                    };
+                    index = 0;
+                }
+            }
+        }
+        Some(core::mem::replace(&mut edge.values[index], value))
+    }
+
+    /// Removes the node at the end of the path along with its descendants.
+    /// The root can't be removed.
+    pub fn remove<I>(&mut self, path: I) -> Option<Self>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<K>,
+    {
+        // find the keys of the edges leading to the node and its index in the
+        // last one
+        let mut path = path.into_iter();
+        let first = path.next()?;
+        let mut edge = self.children.get(first.borrow())?;
+        let mut edge_keys = vec![first];
+        let mut index = 0;
+        for key in path {
+            match edge.tail.get(index) {
+                Some(tail) if tail == key.borrow() => index += 1,
+                Some(_) => return None,
+                None => {
+                    edge = edge.children.get(key.borrow())?;
+                    edge_keys.push(key);
+                    index = 0;
+                }
+            }
+        }
+
+        let (last, edge_keys) =
+            edge_keys.split_last().expect("there is at least one edge");
+        let mut edge_keys = edge_keys.iter();
+        let mut parent = match edge_keys.next() {
+            None => return Some(Edge::detach(&mut self.children, last, index)),
+            Some(key) => self.children.get_mut(key.borrow()),
+        };
+        for key in edge_keys {
+            parent =
+                parent.and_then(|edge| edge.children.get_mut(key.borrow()));
+        }
+        let parent = parent.expect("edges were just found");
+        let removed = Edge::detach(&mut parent.children, last, index);
+        parent.merge_single_child();
+        Some(removed)
+    }
+}
+
+impl<K: Ord, V> Edge<K, V> {
+    /// Creates a chain of nodes for the rest of the path, all but the last one
+    /// having default values
+    fn chain<I>(path: I, value: V) -> Self
+    where
+        I: Iterator<Item = K>,
+        V: Default,
+    {
+        let tail: Vec<K> = path.collect();
+        let mut values: Vec<V> = tail.iter().map(|_| V::default()).collect();
+        values.push(value);
+        Edge {
+            tail,
+            values,
+            children: BTreeMap::new(),
+        }
+    }
+
+    /// Splits the chain so that this edge ends at the node at `index`
+    fn split(&mut self, index: usize) {
+        let tail = self.tail.split_off(index);
+        let values = self.values.split_off(index + 1);
+        let mut tail = tail.into_iter();
+        let key = tail.next().expect("split point must be within the chain");
+        let rest = Edge {
+            tail: tail.collect(),
+            values,
+            children: core::mem::take(&mut self.children),
+        };
+        self.children.insert(key, rest);
+    }
+
+    /// Cuts the chain before the node at `index`, returning it along with its
+    /// descendants
+    fn split_off(&mut self, index: usize) -> RadixTree<K, V> {
+        let mut tail = self.tail.split_off(index - 1).into_iter();
+        let mut values = self.values.split_off(index).into_iter();
+        tail.next();
+        let value = values.next().expect("split point must be within chain");
+        let mut children = core::mem::take(&mut self.children);
+        if let Some(key) = tail.next() {
+            let rest = Edge {
+                tail: tail.collect(),
+                values: values.collect(),
+                children,
+            };
+            children = BTreeMap::new();
+            children.insert(key, rest);
+        }
+        RadixTree { value, children }
+    }
+
+    /// Removes the node at `index` of the edge under `key` along with its
+    /// descendants
+    fn detach<Q: Borrow<K>>(
+        children: &mut BTreeMap<K, Self>,
+        key: &Q,
+        index: usize,
+    ) -> RadixTree<K, V> {
+        if index > 0 {
+            children
+                .get_mut(key.borrow())
+                .expect("edge was just found")
+                .split_off(index)
+        } else {
+            children
+                .remove(key.borrow())
+                .expect("edge was just found")
+                .into_radix_tree()
+        }
+    }
+
+    /// Turns the chain into a tree rooted at its first node
+    fn into_radix_tree(self) -> RadixTree<K, V> {
+        let Edge {
+            tail,
+            values,
+            children,
+        } = self;
+        let mut values = values.into_iter();
+        let value = values.next().expect("chain can't be empty");
+        let mut tail = tail.into_iter();
+        let children = match tail.next() {
+            None => children,
+            Some(key) => {
+                let mut map = BTreeMap::new();
+                map.insert(
+                    key,
+                    Edge {
+                        tail: tail.collect(),
+                        values: values.collect(),
+                        children,
+                    },
+                );
+                map
+            }
+        };
+        RadixTree { value, children }
+    }
+
+    /// Folds the only child into this edge, restoring the invariant that no
+    /// edge ends in a node with a single child
+    fn merge_single_child(&mut self) {
+        if self.children.len() != 1 {
+            return;
+        }
+        let (key, child) = self
+            .children
+            .pop_first()
+            .expect("there is exactly one child");
+        self.tail.push(key);
+        self.tail.extend(child.tail);
+        self.values.extend(child.values);
+        self.children = child.children;
+    }
+
+    /// Follows the chain starting at the tree down for as long as nodes only
+    /// have a single child, returning it without children along with the
+    /// children of its last node
+    fn chain_from(tree: Tree<K, V>) -> (Self, BTreeMap<K, Tree<K, V>>) {
+        let mut tail = Vec::new();
+        let mut values = Vec::new();
+        let mut tree = tree;
+        loop {
+            let (value, mut children) = tree.into_parts();
+            values.push(value);
+            if children.len() != 1 {
+                let edge = Edge {
+                    tail,
+                    values,
+                    children: BTreeMap::new(),
+                };
+                return (edge, children);
+            }
+            let (key, child) =
+                children.pop_first().expect("there is exactly one child");
+            tail.push(key);
+            tree = child;
+        }
+    }
+
+    /// Splits the chain under `key` into the keys along it, the values of all
+    /// but its last node, the value of its last node and the edges below it
+    fn unfold(self, key: K) -> (Vec<K>, Vec<V>, V, BTreeMap<K, Self>) {
+        let Edge {
+            tail,
+            mut values,
+            children,
+        } = self;
+        let keys = core::iter::once(key).chain(tail).collect();
+        let last = values.pop().expect("chain can't be empty");
+        (keys, values, last, children)
+    }
+}
+
+impl<K: Ord, V> From<Tree<K, V>> for RadixTree<K, V> {
+    fn from(tree: Tree<K, V>) -> Self {
+        type Frame<K, V> =
+            (Option<K>, Edge<K, V>, btree_map::IntoIter<K, Tree<K, V>>);
+
+        // the root is kept as a chain of a single node until the end
+        let (value, children) = tree.into_parts();
+        let root = Edge {
+            tail: Vec::new(),
+            values: vec![value],
+            children: BTreeMap::new(),
+        };
+        let mut stack: Vec<Frame<K, V>> =
+            vec![(None, root, children.into_iter())];
+        loop {
+            let (_, _, children) = stack.last_mut().expect("root is last");
+            if let Some((key, child)) = children.next() {
+                let (edge, children) = Edge::chain_from(child);
+                stack.push((Some(key), edge, children.into_iter()));
+                continue;
+            }
+
+            let (key, edge, _) = stack.pop().expect("stack is not empty");
+            match (key, stack.last_mut()) {
+                (Some(key), Some((_, parent, _))) => {
+                    parent.children.insert(key, edge);
+                }
+                _ => {
+                    let Edge {
+                        mut values,
+                        children,
+                        ..
+                    } = edge;
+                    let value = values.pop().expect("root has a value");
+                    return RadixTree { value, children };
+                }
+            }
+        }
+    }
+}
+
+impl<K: Ord, V> From<RadixTree<K, V>> for Tree<K, V> {
+    fn from(tree: RadixTree<K, V>) -> Self {
+        /// The keys and values along a chain, the last node of which is
+        /// built first, and the edges below it
+        type Frame<K, V> = (
+            Vec<K>,
+            Vec<V>,
+            Tree<K, V>,
+            btree_map::IntoIter<K, Edge<K, V>>,
+        );
+
+        let (value, children) = tree.into_parts();
+        let mut stack: Vec<Frame<K, V>> = vec![(
+            Vec::new(),
+            Vec::new(),
+            Tree::new(value),
+            children.into_iter(),
+        )];
+        loop {
+            let (_, _, _, edges) = stack.last_mut().expect("root is last");
+            if let Some((key, edge)) = edges.next() {
+                let (keys, values, last, children) = edge.unfold(key);
+                stack.push((
+                    keys,
+                    values,
+                    Tree::new(last),
+                    children.into_iter(),
+                ));
+                continue;
+            }
+
+            let (mut keys, mut values, mut tree, _) =
+                stack.pop().expect("stack is not empty");
+            let parent = match stack.last_mut() {
+                Some((_, _, parent, _)) => parent,
+                None => return tree,
+            };
+            let mut key = keys.pop().expect("chain can't be empty");
+            while let Some(value) = values.pop() {
+                let mut node = Tree::new(value);
+                node.entry(key).or_insert_tree(tree);
+                tree = node;
+                key = keys.pop().expect("keys and values have the same length");
+            }
+            parent.entry(key).or_insert_tree(tree);
+        }
+    }
+}
+
+impl<K: Ord, V> Drop for RadixTree<K, V> {
+    fn drop(&mut self) {
+        // detach the children of every edge before it is dropped, so no drop
+        // recurses more than one level
+        let mut stack = vec![mem::take(&mut self.children)];
+        while let Some(children) = stack.pop() {
+            for mut edge in children.into_values() {
+                if !edge.children.is_empty() {
+                    stack.push(mem::take(&mut edge.children));
+                }
+            }
+        }
+    }
+}
+
+// Clone, PartialEq, Hash and Debug walk the edges with an explicit stack like
+// the implementations for `Tree`, so deep trees don't overflow the call stack
+
+impl<K: Ord + Clone, V: Clone> Clone for RadixTree<K, V> {
+    fn clone(&self) -> Self {
+        type Frame<'a, K, V> =
+            (Option<K>, Edge<K, V>, btree_map::Iter<'a, K, Edge<K, V>>);
+
+        // the root is kept as a chain of a single node until the end
+        let root = Edge {
+            tail: Vec::new(),
+            values: vec![self.value.clone()],
+            children: BTreeMap::new(),
+        };
+        let mut stack: Vec<Frame<'_, K, V>> =
+            vec![(None, root, self.children.iter())];
+        loop {
+            let (_, _, edges) = stack.last_mut().expect("the root is last");
+            if let Some((key, edge)) = edges.next() {
+                let clone = Edge {
+                    tail: edge.tail.clone(),
+                    values: edge.values.clone(),
+                    children: BTreeMap::new(),
+                };
+                stack.push((Some(key.clone()), clone, edge.children.iter()));
+                continue;
+            }
+
+            let (key, edge, _) = stack.pop().expect("the root is last");
+            match (key, stack.last_mut()) {
+                (Some(key), Some((_, parent, _))) => {
+                    parent.children.insert(key, edge);
+                }
+                _ => {
+                    let Edge {
+                        mut values,
+                        children,
+                        ..
+                    } = edge;
+                    let value = values.pop().expect("root has a value");
+                    return RadixTree { value, children };
+                }
+            }
+        }
+    }
+}
+
+impl<K: Ord, V: PartialEq> PartialEq for RadixTree<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.value != other.value {
+            return false;
+        }
+        let mut stack = vec![(&self.children, &other.children)];
+        while let Some((left, right)) = stack.pop() {
+            if left.len() != right.len() {
+                return false;
+            }
+            for ((left_key, left), (right_key, right)) in left.iter().zip(right)
+            {
+                if left_key != right_key
+                    || left.tail != right.tail
+                    || left.values != right.values
+                {
+                    return false;
+                }
+                stack.push((&left.children, &right.children));
+            }
+        }
+        true
+    }
+}
+
+impl<K: Ord, V: Eq> Eq for RadixTree<K, V> {}
+
+impl<K: Ord + Hash, V: Hash> Hash for RadixTree<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+        let mut stack = vec![&self.children];
+        while let Some(children) = stack.pop() {
+            state.write_usize(children.len());
+            for (key, edge) in children {
+                key.hash(state);
+                edge.tail.hash(state);
+                edge.values.hash(state);
+                stack.push(&edge.children);
+            }
+        }
+    }
+}
+
+/// Formats the tree as a map from the path of every node to its value
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for RadixTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        type Frame<'a, K, V> = (Vec<&'a K>, &'a K, &'a Edge<K, V>);
+
+        let mut map = f.debug_map();
+        map.entry(&Vec::<&K>::new(), &self.value);
+        let mut stack: Vec<Frame<'_, K, V>> = self
+            .children
+            .iter()
+            .rev()
+            .map(|(key, edge)| (Vec::new(), key, edge))
+            .collect();
+        while let Some((mut path, key, edge)) = stack.pop() {
+            for (key, value) in
+                core::iter::once(key).chain(&edge.tail).zip(&edge.values)
+            {
+                path.push(key);
+                map.entry(&path, value);
+            }
+            for (key, child) in edge.children.iter().rev() {
+                stack.push((path.clone(), key, child));
+            }
+        }
+        map.finish()
+    }
+}
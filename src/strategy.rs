@@ -0,0 +1,176 @@
+//! A [proptest](https://docs.rs/proptest) strategy generating trees.
+//!
+//! ```
+//! use proptest::{prelude::*, test_runner::TestRunner};
+//! use tagged_tree::strategy::trees;
+//!
+//! let strategy = trees(0..10u8, any::<i32>(), 3, 4);
+//! TestRunner::default()
+//!     .run(&strategy, |tree| {
+//!         let count = tree.iter_depth_first().count();
+//!         prop_assert_eq!(count, tree.iter_paths().count());
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! ```
+
+use crate::Tree;
+use proptest::{
+    strategy::{NewTree, Strategy, ValueTree},
+    test_runner::TestRunner,
+};
+use std::fmt;
+
+/// Generates trees with keys and values from the given strategies, at most
+/// `max_depth` levels below the root and with at most `max_children`
+/// children per node
+///
+/// Nodes get a random number of children up to the limit, some of which may
+/// be dropped when their keys collide. Shrinking removes subtrees, the ones
+/// closest to the root first, and leaves the keys and values as they are.
+pub fn trees<K, V>(
+    keys: K,
+    values: V,
+    max_depth: usize,
+    max_children: usize,
+) -> TreeStrategy<K, V>
+where
+    K: Strategy,
+    K::Value: Ord + Clone,
+    V: Strategy,
+    V::Value: Clone,
+{
+    TreeStrategy {
+        keys,
+        values,
+        max_depth,
+        max_children,
+    }
+}
+
+/// The strategy returned by [`trees`]
+#[derive(Debug, Clone)]
+pub struct TreeStrategy<K, V> {
+    keys: K,
+    values: V,
+    max_depth: usize,
+    max_children: usize,
+}
+
+impl<K, V> Strategy for TreeStrategy<K, V>
+where
+    K: Strategy,
+    K::Value: Ord + Clone,
+    V: Strategy,
+    V::Value: Clone,
+{
+    type Tree = TreeValueTree<K::Value, V::Value>;
+    type Value = Tree<K::Value, V::Value>;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let mut tree = Tree::new(self.values.new_tree(runner)?.current());
+
+        let mut stack = vec![Vec::new()];
+        while let Some(path) = stack.pop() {
+            if path.len() >= self.max_depth {
+                continue;
+            }
+            let count = (0..=self.max_children).new_tree(runner)?.current();
+            let node = path
+                .iter()
+                .try_fold(&mut tree, |node, key| node.get_child_mut(key))
+                .expect("nodes are added before their children");
+            for _ in 0..count {
+                let key = self.keys.new_tree(runner)?.current();
+                if node.contains_key(&key) {
+                    continue;
+                }
+                let value = self.values.new_tree(runner)?.current();
+                node.add_child(key.clone(), value);
+
+                let mut child = path.clone();
+                child.push(key);
+                stack.push(child);
+            }
+        }
+
+        Ok(TreeValueTree::new(tree))
+    }
+}
+
+/// Shrinks a generated tree by removing its subtrees one at a time
+pub struct TreeValueTree<K: Ord, V> {
+    tree: Tree<K, V>,
+    /// The paths still to be removed, the next one last
+    candidates: Vec<Vec<K>>,
+    removed: Option<(Vec<K>, Tree<K, V>)>,
+}
+
+impl<K: Ord + Clone, V> TreeValueTree<K, V> {
+    fn new(tree: Tree<K, V>) -> Self {
+        let mut candidates: Vec<Vec<K>> = tree
+            .iter_paths()
+            .map(|(path, _)| path.into_iter().cloned().collect())
+            .collect();
+        candidates.sort_by_key(|path| std::cmp::Reverse(path.len()));
+        TreeValueTree {
+            tree,
+            candidates,
+            removed: None,
+        }
+    }
+}
+
+impl<K, V> ValueTree for TreeValueTree<K, V>
+where
+    K: Ord + Clone + fmt::Debug,
+    V: Clone + fmt::Debug,
+{
+    type Value = Tree<K, V>;
+
+    fn current(&self) -> Self::Value {
+        self.tree.clone()
+    }
+
+    fn simplify(&mut self) -> bool {
+        while let Some(mut path) = self.candidates.pop() {
+            let key = path.pop().expect("the root is never a candidate");
+            let subtree = path
+                .iter()
+                .try_fold(&mut self.tree, |node, key| node.get_child_mut(key))
+                .and_then(|parent| parent.remove(&key));
+            // the node is gone already if one of its ancestors was removed
+            if let Some(subtree) = subtree {
+                path.push(key);
+                self.removed = Some((path, subtree));
+                return true;
+            }
+        }
+        false
+    }
+
+    fn complicate(&mut self) -> bool {
+        match self.removed.take() {
+            Some((mut path, subtree)) => {
+                let key = path.pop().expect("the root is never removed");
+                path.iter()
+                    .try_fold(&mut self.tree, |node, key| {
+                        node.get_child_mut(key)
+                    })
+                    .expect("the parent of a removed node is kept")
+                    .entry(key)
+                    .or_insert_tree(subtree);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for TreeValueTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TreeValueTree")
+            .field("tree", &self.tree)
+            .finish()
+    }
+}
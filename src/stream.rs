@@ -0,0 +1,365 @@
+//! Traverses [`LazyTree`]s whose children are loaded asynchronously, for
+//! example from a database, as [`Stream`]s.
+//!
+//! The children of each node are fetched through a [`ChildLoader`] when the
+//! traversal reaches it, unless they were loaded already, and are kept in
+//! the tree afterwards. Up to a given number of loads run at once, the
+//! streams still yield the nodes in the same order as the iterators of
+//! [`Tree`](crate::Tree) do.
+//!
+//! ```
+//! use futures_executor::block_on;
+//! use futures_util::{future, StreamExt};
+//! use tagged_tree::{lazy::LazyTree, stream::ChildLoader};
+//!
+//! struct Numbers;
+//!
+//! impl ChildLoader<u32, u32> for Numbers {
+//!     type Error = ();
+//!     type Future = future::Ready<Result<Vec<(u32, u32)>, ()>>;
+//!
+//!     // the values are the depths of the nodes
+//!     fn load_children(&self, _path: &[u32], depth: &u32) -> Self::Future {
+//!         let children = match depth {
+//!             0 | 1 => vec![(1, depth + 1), (2, depth + 1)],
+//!             _ => Vec::new(),
+//!         };
+//!         future::ready(Ok(children))
+//!     }
+//! }
+//!
+//! let tree = LazyTree::new(0, Numbers);
+//! let paths: Vec<_> = block_on(
+//!     tree.stream_depth_first(4)
+//!         .map(|node| node.unwrap().path().to_vec())
+//!         .collect(),
+//! );
+//! assert_eq!(
+//!     paths,
+//!     [vec![1], vec![1, 1], vec![1, 2], vec![2], vec![2, 1], vec![2, 2]],
+//! );
+//! assert!(tree.is_expanded());
+//! ```
+
+use crate::lazy::LazyTree;
+use alloc::{
+    collections::{BTreeMap, VecDeque},
+    vec::Vec,
+};
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures_util::stream::{FusedStream, FuturesOrdered, Stream, StreamExt};
+
+/// Loads the children of the nodes of a [`LazyTree`] asynchronously
+pub trait ChildLoader<K, V> {
+    type Error;
+    type Future: Future<Output = Result<Vec<(K, V)>, Self::Error>>;
+
+    /// Loads the children of the node with the given value at the end of
+    /// `path`, the root being at the end of an empty one
+    ///
+    /// The children are visited in the order of their keys and when a key is
+    /// repeated, the last value is kept.
+    fn load_children(&self, path: &[K], value: &V) -> Self::Future;
+}
+
+impl<K: Ord + Clone, V, L: ChildLoader<K, V>> LazyTree<K, V, L> {
+    /// Visits the nodes below this one in depth first order, loading the
+    /// children of up to `concurrency` nodes at once
+    ///
+    /// The nodes that are next in line are loaded ahead of time. A failed
+    /// load is yielded as an error in place of the children of its node, and
+    /// the traversal carries on with the next one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `concurrency` is zero.
+    #[inline]
+    pub fn stream_depth_first(
+        &self,
+        concurrency: usize,
+    ) -> DepthFirstStream<'_, K, V, L> {
+        DepthFirstStream {
+            loads: Loads::new(self.loader(), concurrency),
+            stack: Vec::new(),
+            current: Some((self, None)),
+        }
+    }
+
+    /// Visits the nodes below this one in breadth first order, loading the
+    /// children of up to `concurrency` nodes at once
+    ///
+    /// A failed load is yielded as an error in place of the children of its
+    /// node, and the traversal carries on with the next one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `concurrency` is zero.
+    #[inline]
+    pub fn stream_breadth_first(
+        &self,
+        concurrency: usize,
+    ) -> BreadthFirstStream<'_, K, V, L> {
+        let mut pending = VecDeque::new();
+        pending.push_back((self, None));
+        BreadthFirstStream {
+            loads: Loads::new(self.loader(), concurrency),
+            pending,
+            ready: VecDeque::new(),
+        }
+    }
+}
+
+type Loaded<K, V, E> = Result<Vec<(K, V)>, E>;
+
+type Children<'n, K, V, L> =
+    Result<&'n BTreeMap<K, LazyTree<K, V, L>>, <L as ChildLoader<K, V>>::Error>;
+
+/// The loads in flight and the results that weren't needed yet
+struct Loads<'a, K, V, L: ChildLoader<K, V>> {
+    loader: &'a L,
+    concurrency: usize,
+    futures: FuturesOrdered<L::Future>,
+    /// The ids of the loads in flight, in the order they were started
+    ids: VecDeque<usize>,
+    done: BTreeMap<usize, Loaded<K, V, L::Error>>,
+    next_id: usize,
+}
+
+impl<'a, K: Ord, V, L: ChildLoader<K, V>> Loads<'a, K, V, L> {
+    fn new(loader: &'a L, concurrency: usize) -> Self {
+        assert!(concurrency > 0, "at least one load must be allowed");
+        Loads {
+            loader,
+            concurrency,
+            futures: FuturesOrdered::new(),
+            ids: VecDeque::new(),
+            done: BTreeMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Starts loading the children of the node unless they were loaded
+    /// already or too many loads are in flight
+    fn start(&mut self, node: &LazyTree<K, V, L>) -> Option<usize> {
+        if node.is_expanded() || self.futures.len() >= self.concurrency {
+            return None;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.futures
+            .push_back(self.loader.load_children(node.path(), node.value()));
+        self.ids.push_back(id);
+        Some(id)
+    }
+
+    #[inline]
+    fn take(&mut self, id: Option<usize>) -> Option<Loaded<K, V, L::Error>> {
+        id.and_then(|id| self.done.remove(&id))
+    }
+
+    /// Waits for the oldest load in flight
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        match self.futures.poll_next_unpin(cx) {
+            Poll::Ready(Some(loaded)) => {
+                let id = self.ids.pop_front().expect("every load has an id");
+                self.done.insert(id, loaded);
+                Poll::Ready(())
+            }
+            Poll::Ready(None) => {
+                unreachable!("only waited on while a load is in flight")
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    /// The children of the node, if they were loaded, storing them in the
+    /// tree the first time
+    fn children<'n>(
+        &mut self,
+        node: &'n LazyTree<K, V, L>,
+        id: Option<usize>,
+    ) -> Option<Children<'n, K, V, L>>
+    where
+        K: Clone,
+    {
+        match node.loaded_children() {
+            Some(children) => Some(Ok(children)),
+            None => Some(self.take(id)?.map(|loaded| node.expand(loaded))),
+        }
+    }
+}
+
+/// A node and the id of the load of its children, if it started
+type Frame<'a, K, V, L> = (&'a LazyTree<K, V, L>, Option<usize>);
+
+/// Visits the nodes of a [`LazyTree`] in depth first order
+pub struct DepthFirstStream<'a, K: Ord, V, L: ChildLoader<K, V>> {
+    loads: Loads<'a, K, V, L>,
+    /// The nodes still to be visited, the next one last
+    stack: Vec<Frame<'a, K, V, L>>,
+    /// The node whose children are visited next
+    current: Option<Frame<'a, K, V, L>>,
+}
+
+impl<'a, K, V, L> Stream for DepthFirstStream<'a, K, V, L>
+where
+    K: Ord + Clone,
+    L: ChildLoader<K, V>,
+{
+    type Item = Result<&'a LazyTree<K, V, L>, L::Error>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match this.current.take() {
+                Some((node, id)) => {
+                    let id = id.or_else(|| this.loads.start(node));
+                    match this.loads.children(node, id) {
+                        Some(Ok(children)) => {
+                            for child in children.values().rev() {
+                                this.stack.push((child, None));
+                            }
+                            continue;
+                        }
+                        Some(Err(error)) => {
+                            return Poll::Ready(Some(Err(error)))
+                        }
+                        None => this.current = Some((node, id)),
+                    }
+                }
+                None => match this.stack.pop() {
+                    Some((node, id)) => {
+                        let id = id.or_else(|| this.loads.start(node));
+                        this.current = Some((node, id));
+                        return Poll::Ready(Some(Ok(node)));
+                    }
+                    None => return Poll::Ready(None),
+                },
+            }
+
+            // load the nodes that come after the current one ahead of time
+            let concurrency = this.loads.concurrency;
+            for (node, id) in this.stack.iter_mut().rev().take(concurrency) {
+                if id.is_none() {
+                    *id = this.loads.start(node);
+                }
+            }
+
+            if this.loads.poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+        }
+    }
+}
+
+impl<K, V, L> FusedStream for DepthFirstStream<'_, K, V, L>
+where
+    K: Ord + Clone,
+    L: ChildLoader<K, V>,
+{
+    #[inline]
+    fn is_terminated(&self) -> bool {
+        self.current.is_none() && self.stack.is_empty()
+    }
+}
+
+impl<K: Ord, V, L: ChildLoader<K, V>> fmt::Debug
+    for DepthFirstStream<'_, K, V, L>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DepthFirstStream")
+            .field("pending", &self.stack.len())
+            .field("in_flight", &self.loads.futures.len())
+            .finish()
+    }
+}
+
+/// Visits the nodes of a [`LazyTree`] in breadth first order
+pub struct BreadthFirstStream<'a, K: Ord, V, L: ChildLoader<K, V>> {
+    loads: Loads<'a, K, V, L>,
+    /// The nodes whose children are visited next, in order
+    pending: VecDeque<Frame<'a, K, V, L>>,
+    /// The nodes that were loaded but not visited yet
+    ready: VecDeque<&'a LazyTree<K, V, L>>,
+}
+
+impl<'a, K, V, L> Stream for BreadthFirstStream<'a, K, V, L>
+where
+    K: Ord + Clone,
+    L: ChildLoader<K, V>,
+{
+    type Item = Result<&'a LazyTree<K, V, L>, L::Error>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(node) = this.ready.pop_front() {
+                return Poll::Ready(Some(Ok(node)));
+            }
+
+            let concurrency = this.loads.concurrency;
+            for (node, id) in this.pending.iter_mut().take(concurrency) {
+                if id.is_none() {
+                    *id = this.loads.start(node);
+                }
+            }
+
+            let (node, id) = match this.pending.front() {
+                Some(frame) => *frame,
+                None => return Poll::Ready(None),
+            };
+            match this.loads.children(node, id) {
+                Some(loaded) => {
+                    this.pending.pop_front();
+                    match loaded {
+                        Ok(children) => {
+                            for child in children.values() {
+                                this.ready.push_back(child);
+                                this.pending.push_back((child, None));
+                            }
+                        }
+                        Err(error) => return Poll::Ready(Some(Err(error))),
+                    }
+                }
+                None => {
+                    if this.loads.poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<K, V, L> FusedStream for BreadthFirstStream<'_, K, V, L>
+where
+    K: Ord + Clone,
+    L: ChildLoader<K, V>,
+{
+    #[inline]
+    fn is_terminated(&self) -> bool {
+        self.ready.is_empty() && self.pending.is_empty()
+    }
+}
+
+impl<K: Ord, V, L: ChildLoader<K, V>> fmt::Debug
+    for BreadthFirstStream<'_, K, V, L>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BreadthFirstStream")
+            .field("pending", &(self.ready.len() + self.pending.len()))
+            .field("in_flight", &self.loads.futures.len())
+            .finish()
+    }
+}
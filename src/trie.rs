@@ -0,0 +1,261 @@
+use crate::Tree;
+use alloc::{vec, vec::Vec};
+use core::{borrow::Borrow, iter::FusedIterator};
+
+/// A trie keyed by sequences of `K`, backed by a `Tree<K, Option<V>>`.
+///
+/// Only nodes on the way to a value are kept, branches left empty by
+/// [`Trie::remove`] are pruned.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Trie<K: Ord, V> {
+    tree: Tree<K, Option<V>>,
+    len: usize,
+}
+
+impl<K: Ord, V> Trie<K, V> {
+    #[inline]
+    pub fn new() -> Self {
+        Trie {
+            tree: Tree::new(None),
+            len: 0,
+        }
+    }
+
+    /// The number of sequences stored in the trie
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        *self = Trie::new();
+    }
+
+    #[inline]
+    pub fn as_tree(&self) -> &Tree<K, Option<V>> {
+        &self.tree
+    }
+
+    #[inline]
+    pub fn into_tree(self) -> Tree<K, Option<V>> {
+        self.tree
+    }
+
+    /// Inserts a value for the sequence, returning the previous one if any
+    pub fn insert<I>(&mut self, seq: I, value: V) -> Option<V>
+    where
+        I: IntoIterator<Item = K>,
+    {
+        let node = seq
+            .into_iter()
+            .fold(&mut self.tree, |node, key| node.entry(key).or_insert(None));
+        let old = node.set_value(Some(value));
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn get<I>(&self, seq: I) -> Option<&V>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<K>,
+    {
+        self.node(seq)?.value().as_ref()
+    }
+
+    pub fn get_mut<I>(&mut self, seq: I) -> Option<&mut V>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<K>,
+    {
+        seq.into_iter()
+            .try_fold(&mut self.tree, |node, key| {
+                node.get_child_mut(key.borrow())
+            })?
+            .value_mut()
+            .as_mut()
+    }
+
+    #[inline]
+    pub fn contains<I>(&self, seq: I) -> bool
+    where
+        I: IntoIterator,
+        I::Item: Borrow<K>,
+    {
+        self.get(seq).is_some()
+    }
+
+    /// Checks if any stored sequence starts with the prefix
+    #[inline]
+    pub fn starts_with<I>(&self, prefix: I) -> bool
+    where
+        I: IntoIterator,
+        I::Item: Borrow<K>,
+    {
+        // empty branches are always pruned, so any node leads to a value,
+        // except for the root which is there even when nothing is stored
+        !self.is_empty() && self.node(prefix).is_some()
+    }
+
+    /// Finds the longest stored sequence that is a prefix of `seq`, returning
+    /// its length and value
+    pub fn longest_prefix_match<I>(&self, seq: I) -> Option<(usize, &V)>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<K>,
+    {
+        let mut node = &self.tree;
+        let mut longest = node.value().as_ref().map(|value| (0, value));
+        for (depth, key) in seq.into_iter().enumerate() {
+            node = match node.get_child(key.borrow()) {
+                Some(child) => child,
+                None => break,
+            };
+            if let Some(value) = node.value() {
+                longest = Some((depth + 1, value));
+            }
+        }
+        longest
+    }
+
+    /// An iterator visiting the stored sequences that start with the prefix
+    /// in depth first order
+    pub fn iter_prefix<I>(&self, prefix: I) -> TrieIter<'_, K, V>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<K>,
+    {
+        let mut path = Vec::new();
+        let mut node = &self.tree;
+        for key in prefix {
+            match node.get_key_value(key.borrow()) {
+                Some((key, child)) => {
+                    path.push(key);
+                    node = child;
+                }
+                None => return TrieIter { stack: Vec::new() },
+            }
+        }
+        TrieIter {
+            stack: vec![(path, node)],
+        }
+    }
+
+    /// An iterator visiting all the stored sequences in depth first order
+    #[inline]
+    pub fn iter(&self) -> TrieIter<'_, K, V> {
+        TrieIter {
+            stack: vec![(Vec::new(), &self.tree)],
+        }
+    }
+
+    /// Removes the sequence from the trie, pruning any branch left without
+    /// values
+    pub fn remove<I>(&mut self, seq: I) -> Option<V>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<K>,
+    {
+        let seq: Vec<I::Item> = seq.into_iter().collect();
+        let node = seq.iter().try_fold(&mut self.tree, |node, key| {
+            node.get_child_mut(key.borrow())
+        })?;
+        let value = node.value_mut().take()?;
+        self.len -= 1;
+        if node.is_childless() && !seq.is_empty() {
+            self.prune(&seq);
+        }
+        Some(value)
+    }
+
+    /// Removes the longest empty branch at the end of the sequence
+    fn prune<Q: Borrow<K>>(&mut self, seq: &[Q]) {
+        let mut keep = 0;
+        let mut node = &self.tree;
+        for (depth, key) in seq.iter().enumerate() {
+            if node.value().is_some() || node.children().len() > 1 {
+                keep = depth;
+            }
+            node = node
+                .get_child(key.borrow())
+                .expect("pruned sequence should exist");
+        }
+
+        let parent = seq[..keep].iter().fold(&mut self.tree, |node, key| {
+            node.get_child_mut(key.borrow())
+                .expect("pruned sequence should exist")
+        });
+        parent.remove(seq[keep].borrow());
+    }
+
+    fn node<I>(&self, seq: I) -> Option<&Tree<K, Option<V>>>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<K>,
+    {
+        seq.into_iter()
+            .try_fold(&self.tree, |node, key| node.get_child(key.borrow()))
+    }
+}
+
+impl<K: Ord, V> Default for Trie<K, V> {
+    #[inline]
+    fn default() -> Self {
+        Trie::new()
+    }
+}
+
+impl<K: Ord, V> From<Trie<K, V>> for Tree<K, Option<V>> {
+    #[inline]
+    fn from(trie: Trie<K, V>) -> Self {
+        trie.tree
+    }
+}
+
+impl<K: Ord, V, I> core::iter::FromIterator<(I, V)> for Trie<K, V>
+where
+    I: IntoIterator<Item = K>,
+{
+    fn from_iter<T: IntoIterator<Item = (I, V)>>(iter: T) -> Self {
+        let mut trie = Trie::new();
+        for (seq, value) in iter {
+            trie.insert(seq, value);
+        }
+        trie
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TrieIter<'a, K: Ord + 'a, V: 'a> {
+    stack: Vec<Frame<'a, K, V>>,
+}
+
+/// A node of the backing tree along with its key path
+type Frame<'a, K, V> = (Vec<&'a K>, &'a Tree<K, Option<V>>);
+
+impl<'a, K: Ord + 'a, V: 'a> Iterator for TrieIter<'a, K, V> {
+    type Item = (Vec<&'a K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((path, tree)) = self.stack.pop() {
+            for (key, child) in tree.iter_single().rev() {
+                let mut path = path.clone();
+                path.push(key);
+                self.stack.push((path, child));
+            }
+            if let Some(value) = tree.value() {
+                return Some((path, value));
+            }
+        }
+        None
+    }
+}
+
+impl<K: Ord, V> FusedIterator for TrieIter<'_, K, V> {}
@@ -0,0 +1,561 @@
+//! A dynamically typed value, for trees holding data of different kinds, and
+//! conversions between such trees and Rust types.
+//!
+//! Structs are converted by [`IntoTree`] and [`FromTree`], which can be
+//! derived with the `derive` feature. Each field becomes a child keyed by its
+//! name, so nested structs become nested subtrees, while the struct's own node
+//! holds [`Value::Null`].
+//!
+//! ```
+//! use tagged_tree::{
+//!     value::{FromTree, IntoTree, Value},
+//!     Tree,
+//! };
+//!
+//! let mut tree = Tree::new(Value::Null);
+//! tree.add_child("port", Value::Int(8080));
+//! tree.add_child("verbose", Value::Bool(true));
+//!
+//! let port = u16::from_tree(&tree[&"port"]).unwrap();
+//! assert_eq!(port, 8080);
+//! assert_eq!(port.into_tree(), tree[&"port"]);
+//! ```
+
+use crate::Tree;
+#[cfg(feature = "serde")]
+use alloc::string::ToString;
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+use core::{convert::TryFrom, fmt};
+#[cfg(feature = "serde")]
+use serde::{
+    de::{self, Deserialize, Deserializer, Visitor},
+    ser::{self, Serialize, Serializer},
+};
+
+#[cfg(feature = "serde")]
+mod deserializer;
+#[cfg(feature = "serde")]
+pub mod nested;
+#[cfg(feature = "serde")]
+mod serializer;
+
+#[cfg(feature = "serde")]
+pub use deserializer::from_tree;
+#[cfg(feature = "serde")]
+pub use serializer::to_tree;
+
+#[cfg(feature = "derive")]
+pub use tagged_tree_derive::{FromTree, IntoTree};
+
+/// A value of one of the basic data types
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+impl Value {
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Int(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    /// Returns floats, and integers converted to floats
+    #[inline]
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Int(i) => Some(i as f64),
+            Value::Float(f) => Some(f),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Value {
+    #[inline]
+    fn default() -> Self {
+        Value::Null
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::String(s) => write!(f, "{:?}", s),
+            Value::Bytes(b) => write!(f, "{:?}", b),
+        }
+    }
+}
+
+/// The error returned by [`to_tree`] and [`from_tree`]
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Error {
+    message: String,
+}
+
+#[cfg(feature = "serde")]
+impl Error {
+    #[inline]
+    fn new(message: impl Into<String>) -> Self {
+        Error {
+            message: message.into(),
+        }
+    }
+
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// `std::error::Error` when the `std` feature is enabled
+#[cfg(feature = "serde")]
+impl serde::de::StdError for Error {}
+
+#[cfg(feature = "serde")]
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::new(msg.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::new(msg.to_string())
+    }
+}
+
+/// Serializes as the matching serde data type, null being a unit
+#[cfg(feature = "serde")]
+impl Serialize for Value {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::Int(i) => serializer.serialize_i64(*i),
+            Value::Float(x) => serializer.serialize_f64(*x),
+            Value::String(s) => serializer.serialize_str(s),
+            Value::Bytes(b) => serializer.serialize_bytes(b),
+        }
+    }
+}
+
+/// Deserializes any scalar, but not sequences or maps
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct ValueVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a null, bool, number, string or bytes")
+    }
+
+    fn visit_bool<E: de::Error>(self, b: bool) -> Result<Value, E> {
+        Ok(Value::Bool(b))
+    }
+
+    fn visit_i64<E: de::Error>(self, i: i64) -> Result<Value, E> {
+        Ok(Value::Int(i))
+    }
+
+    fn visit_u64<E: de::Error>(self, u: u64) -> Result<Value, E> {
+        i64::try_from(u).map(Value::Int).map_err(|_| {
+            E::invalid_value(de::Unexpected::Unsigned(u), &"a 64 bit integer")
+        })
+    }
+
+    fn visit_f64<E: de::Error>(self, x: f64) -> Result<Value, E> {
+        Ok(Value::Float(x))
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Value, E> {
+        Ok(Value::String(s.to_owned()))
+    }
+
+    fn visit_string<E: de::Error>(self, s: String) -> Result<Value, E> {
+        Ok(Value::String(s))
+    }
+
+    fn visit_bytes<E: de::Error>(self, b: &[u8]) -> Result<Value, E> {
+        Ok(Value::Bytes(b.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, b: Vec<u8>) -> Result<Value, E> {
+        Ok(Value::Bytes(b))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+}
+
+impl From<()> for Value {
+    #[inline]
+    fn from(_: ()) -> Self {
+        Value::Null
+    }
+}
+
+impl From<bool> for Value {
+    #[inline]
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<String> for Value {
+    #[inline]
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
+}
+
+impl From<&str> for Value {
+    #[inline]
+    fn from(s: &str) -> Self {
+        Value::String(s.to_owned())
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    #[inline]
+    fn from(b: Vec<u8>) -> Self {
+        Value::Bytes(b)
+    }
+}
+
+impl From<&[u8]> for Value {
+    #[inline]
+    fn from(b: &[u8]) -> Self {
+        Value::Bytes(b.to_vec())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    #[inline]
+    fn from(option: Option<T>) -> Self {
+        option.map_or(Value::Null, Into::into)
+    }
+}
+
+/// Types that can be turned into a tree of values
+pub trait IntoTree {
+    fn into_tree(self) -> Tree<&'static str, Value>;
+}
+
+/// Types that can be read back from a tree of values
+pub trait FromTree: Sized {
+    fn from_tree(
+        tree: &Tree<&'static str, Value>,
+    ) -> Result<Self, FromTreeError>;
+
+    /// Called instead of [`from_tree`](FromTree::from_tree) when the field
+    /// holding the value has no node. Fails unless overridden.
+    #[inline]
+    fn from_missing(field: &'static str) -> Result<Self, FromTreeError> {
+        Err(FromTreeError::new("missing field").in_field(field))
+    }
+}
+
+/// The error returned when a tree doesn't hold the expected values
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FromTreeError {
+    path: Vec<&'static str>,
+    message: &'static str,
+}
+
+impl FromTreeError {
+    #[inline]
+    pub fn new(message: &'static str) -> Self {
+        FromTreeError {
+            path: Vec::new(),
+            message,
+        }
+    }
+
+    /// Marks the error as coming from the given field, for errors returned
+    /// by the fields of a struct
+    pub fn in_field(mut self, field: &'static str) -> Self {
+        self.path.insert(0, field);
+        self
+    }
+
+    /// The keys leading to the node that couldn't be converted
+    #[inline]
+    pub fn path(&self) -> &[&'static str] {
+        &self.path
+    }
+
+    #[inline]
+    pub fn message(&self) -> &'static str {
+        self.message
+    }
+}
+
+impl fmt::Display for FromTreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(self.message)
+        } else {
+            write!(f, "{} at `{}`", self.message, self.path.join("."))
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FromTreeError {}
+
+impl IntoTree for Tree<&'static str, Value> {
+    #[inline]
+    fn into_tree(self) -> Tree<&'static str, Value> {
+        self
+    }
+}
+
+impl FromTree for Tree<&'static str, Value> {
+    #[inline]
+    fn from_tree(
+        tree: &Tree<&'static str, Value>,
+    ) -> Result<Self, FromTreeError> {
+        Ok(tree.clone())
+    }
+}
+
+impl IntoTree for Value {
+    #[inline]
+    fn into_tree(self) -> Tree<&'static str, Value> {
+        Tree::new(self)
+    }
+}
+
+impl FromTree for Value {
+    #[inline]
+    fn from_tree(
+        tree: &Tree<&'static str, Value>,
+    ) -> Result<Self, FromTreeError> {
+        Ok(tree.value.clone())
+    }
+}
+
+impl IntoTree for bool {
+    #[inline]
+    fn into_tree(self) -> Tree<&'static str, Value> {
+        Tree::new(Value::Bool(self))
+    }
+}
+
+impl FromTree for bool {
+    #[inline]
+    fn from_tree(
+        tree: &Tree<&'static str, Value>,
+    ) -> Result<Self, FromTreeError> {
+        tree.value
+            .as_bool()
+            .ok_or_else(|| FromTreeError::new("expected a bool"))
+    }
+}
+
+macro_rules! integer {
+    (@from $t:ty) => {
+        impl FromTree for $t {
+            fn from_tree(
+                tree: &Tree<&'static str, Value>,
+            ) -> Result<Self, FromTreeError> {
+                let i = tree
+                    .value
+                    .as_i64()
+                    .ok_or_else(|| FromTreeError::new("expected an integer"))?;
+                <$t>::try_from(i)
+                    .map_err(|_| FromTreeError::new("integer out of range"))
+            }
+        }
+    };
+    ($($t:ty)*) => {$(
+        impl IntoTree for $t {
+            #[inline]
+            fn into_tree(self) -> Tree<&'static str, Value> {
+                Tree::new(Value::Int(self.into()))
+            }
+        }
+
+        integer!(@from $t);
+    )*};
+}
+
+integer!(i8 i16 i32 i64 u8 u16 u32);
+
+impl IntoTree for isize {
+    #[inline]
+    fn into_tree(self) -> Tree<&'static str, Value> {
+        // Rust has no targets where `isize` is wider than 64 bits
+        Tree::new(Value::Int(self as i64))
+    }
+}
+
+// `u64` and `usize` can be read back, but not turned into trees, as values
+// above `i64::MAX` don't fit in a `Value::Int`
+integer!(@from u64);
+integer!(@from usize);
+integer!(@from isize);
+
+macro_rules! float {
+    ($($t:ty)*) => {$(
+        impl IntoTree for $t {
+            #[inline]
+            fn into_tree(self) -> Tree<&'static str, Value> {
+                Tree::new(Value::Float(self.into()))
+            }
+        }
+
+        impl FromTree for $t {
+            #[inline]
+            fn from_tree(
+                tree: &Tree<&'static str, Value>,
+            ) -> Result<Self, FromTreeError> {
+                tree.value
+                    .as_f64()
+                    .map(|x| x as $t)
+                    .ok_or_else(|| FromTreeError::new("expected a float"))
+            }
+        }
+    )*};
+}
+
+float!(f32 f64);
+
+impl IntoTree for String {
+    #[inline]
+    fn into_tree(self) -> Tree<&'static str, Value> {
+        Tree::new(Value::String(self))
+    }
+}
+
+impl FromTree for String {
+    #[inline]
+    fn from_tree(
+        tree: &Tree<&'static str, Value>,
+    ) -> Result<Self, FromTreeError> {
+        tree.value
+            .as_str()
+            .map(str::to_owned)
+            .ok_or_else(|| FromTreeError::new("expected a string"))
+    }
+}
+
+impl IntoTree for Vec<u8> {
+    #[inline]
+    fn into_tree(self) -> Tree<&'static str, Value> {
+        Tree::new(Value::Bytes(self))
+    }
+}
+
+impl FromTree for Vec<u8> {
+    #[inline]
+    fn from_tree(
+        tree: &Tree<&'static str, Value>,
+    ) -> Result<Self, FromTreeError> {
+        tree.value
+            .as_bytes()
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| FromTreeError::new("expected bytes"))
+    }
+}
+
+/// `None` is a null value, which is also what a missing field is read as
+impl<T: IntoTree> IntoTree for Option<T> {
+    #[inline]
+    fn into_tree(self) -> Tree<&'static str, Value> {
+        self.map_or_else(|| Tree::new(Value::Null), IntoTree::into_tree)
+    }
+}
+
+impl<T: FromTree> FromTree for Option<T> {
+    fn from_tree(
+        tree: &Tree<&'static str, Value>,
+    ) -> Result<Self, FromTreeError> {
+        if tree.value.is_null() && tree.is_childless() {
+            Ok(None)
+        } else {
+            T::from_tree(tree).map(Some)
+        }
+    }
+
+    #[inline]
+    fn from_missing(_: &'static str) -> Result<Self, FromTreeError> {
+        Ok(None)
+    }
+}
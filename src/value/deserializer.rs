@@ -0,0 +1,386 @@
+use super::{Error, Value};
+use crate::Tree;
+use alloc::{
+    collections::btree_map,
+    string::{String, ToString},
+};
+use serde::{
+    de::{
+        self, value::BorrowedStrDeserializer, DeserializeSeed, EnumAccess,
+        MapAccess, SeqAccess, VariantAccess, Visitor,
+    },
+    forward_to_deserialize_any, Deserialize,
+};
+
+/// Deserializes a value from a tree laid out like the ones built by
+/// [`to_tree`](super::to_tree)
+///
+/// Childless nodes holding [`Value::Null`] are read as empty sequences and
+/// maps as well as unit values, since that's what those become in a tree.
+///
+/// ```
+/// use serde::Deserialize;
+/// use tagged_tree::{value::Value, Tree};
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// struct Server {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// let mut tree = Tree::new(Value::Null);
+/// tree.add_child("host".to_owned(), Value::from("localhost"));
+/// tree.add_child("port".to_owned(), Value::Int(8080));
+///
+/// let server: Server = tagged_tree::from_tree(&tree).unwrap();
+/// assert_eq!(server.port, 8080);
+/// ```
+pub fn from_tree<'de, T: Deserialize<'de>>(
+    tree: &'de Tree<String, Value>,
+) -> core::result::Result<T, Error> {
+    T::deserialize(Deserializer(tree))
+}
+
+type Result<T> = core::result::Result<T, Error>;
+
+struct Deserializer<'de>(&'de Tree<String, Value>);
+
+impl<'de> Deserializer<'de> {
+    /// Whether the node stands for nothing, which is also how empty
+    /// sequences and maps end up
+    fn is_empty(&self) -> bool {
+        self.0.value.is_null() && self.0.is_childless()
+    }
+
+    fn visit_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if !self.0.value.is_null() {
+            return Err(de::Error::invalid_type(self.unexpected(), &visitor));
+        }
+        visitor.visit_map(Map {
+            children: self.0.children.iter(),
+            child: None,
+        })
+    }
+
+    fn unexpected(&self) -> de::Unexpected<'de> {
+        use de::Unexpected;
+
+        if !self.0.is_childless() {
+            return Unexpected::Map;
+        }
+        match &self.0.value {
+            Value::Null => Unexpected::Unit,
+            Value::Bool(b) => Unexpected::Bool(*b),
+            Value::Int(i) => Unexpected::Signed(*i),
+            Value::Float(x) => Unexpected::Float(*x),
+            Value::String(s) => Unexpected::Str(s),
+            Value::Bytes(b) => Unexpected::Bytes(b),
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if !self.0.is_childless() {
+            return self.visit_map(visitor);
+        }
+        match &self.0.value {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(*b),
+            Value::Int(i) => visitor.visit_i64(*i),
+            Value::Float(x) => visitor.visit_f64(*x),
+            Value::String(s) => visitor.visit_borrowed_str(s),
+            Value::Bytes(b) => visitor.visit_borrowed_bytes(b),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value> {
+        if self.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Reads the children keyed `0`, `1` and so on, in numeric order
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if !self.0.value.is_null() {
+            return Err(de::Error::invalid_type(self.unexpected(), &visitor));
+        }
+        let mut seq = Seq {
+            tree: self.0,
+            index: 0,
+        };
+        let value = visitor.visit_seq(&mut seq)?;
+        if seq.index == self.0.children.len() {
+            Ok(value)
+        } else {
+            Err(Error::new(
+                "sequence nodes must be keyed by the index of each element",
+            ))
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.visit_map(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.visit_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match (&self.0.value, self.0.first_child()) {
+            (Value::String(name), None) => visitor.visit_enum(Enum {
+                name,
+                content: None,
+            }),
+            (Value::Null, Some((name, content)))
+                if self.0.children.len() == 1 =>
+            {
+                visitor.visit_enum(Enum {
+                    name,
+                    content: Some(content),
+                })
+            }
+            _ => Err(Error::new(
+                "expected a variant name, or a node with a single child keyed \
+                 by the variant name",
+            )),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct identifier
+    }
+}
+
+struct Seq<'de> {
+    tree: &'de Tree<String, Value>,
+    index: usize,
+}
+
+impl<'de> SeqAccess<'de> for Seq<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>> {
+        match self.tree.children.get(&self.index.to_string()) {
+            Some(child) => {
+                self.index += 1;
+                seed.deserialize(Deserializer(child)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+struct Map<'de> {
+    children: btree_map::Iter<'de, String, Tree<String, Value>>,
+    child: Option<&'de Tree<String, Value>>,
+}
+
+impl<'de> MapAccess<'de> for Map<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>> {
+        match self.children.next() {
+            Some((key, child)) => {
+                self.child = Some(child);
+                seed.deserialize(Key(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value> {
+        let child = self
+            .child
+            .take()
+            .ok_or_else(|| Error::new("map value without a key"))?;
+        seed.deserialize(Deserializer(child))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.children.len())
+    }
+}
+
+/// Reads map keys, parsing them when numbers or booleans are asked for
+struct Key<'de>(&'de str);
+
+macro_rules! parse_key {
+    ($($method:ident $visit:ident)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+            match self.0.parse() {
+                Ok(key) => visitor.$visit(key),
+                Err(_) => self.deserialize_any(visitor),
+            }
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for Key<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_enum(Enum {
+            name: self.0,
+            content: None,
+        })
+    }
+
+    parse_key! {
+        deserialize_bool visit_bool
+        deserialize_i8 visit_i8
+        deserialize_i16 visit_i16
+        deserialize_i32 visit_i32
+        deserialize_i64 visit_i64
+        deserialize_i128 visit_i128
+        deserialize_u8 visit_u8
+        deserialize_u16 visit_u16
+        deserialize_u32 visit_u32
+        deserialize_u64 visit_u64
+        deserialize_u128 visit_u128
+    }
+
+    forward_to_deserialize_any! {
+        f32 f64 char str string bytes byte_buf option unit unit_struct seq
+        tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct Enum<'de> {
+    name: &'de str,
+    content: Option<&'de Tree<String, Value>>,
+}
+
+impl<'de> EnumAccess<'de> for Enum<'de> {
+    type Error = Error;
+    type Variant = Variant<'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant)> {
+        let name = BorrowedStrDeserializer::<Error>::new(self.name);
+        Ok((seed.deserialize(name)?, Variant(self.content)))
+    }
+}
+
+struct Variant<'de>(Option<&'de Tree<String, Value>>);
+
+impl<'de> Variant<'de> {
+    fn content(self) -> Result<Deserializer<'de>> {
+        self.0
+            .map(Deserializer)
+            .ok_or_else(|| Error::new("expected a variant with data"))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Variant<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        match self.0 {
+            None => Ok(()),
+            Some(_) => Err(Error::new("expected a unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value> {
+        seed.deserialize(self.content()?)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_seq(self.content()?, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_map(self.content()?, visitor)
+    }
+}
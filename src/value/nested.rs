@@ -0,0 +1,196 @@
+//! Serializes a tree of values as nested data, the way JSON documents are
+//! usually laid out.
+//!
+//! Childless nodes are written as their value and the other nodes as maps of
+//! their children, so only childless nodes can hold a value other than
+//! [`Value::Null`]. When reading, sequences become nodes keyed by the index of
+//! each element, so they are written back as maps. Maps keyed by indices
+//! only are written in index order, so `"10"` comes after `"9"`.
+//!
+//! Use it with `#[serde(with = "tagged_tree::value::nested")]`.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use serde_json::json;
+//! use tagged_tree::{value::Value, Tree};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Document(
+//!     #[serde(with = "tagged_tree::value::nested")] Tree<String, Value>,
+//! );
+//!
+//! let json = json!({ "server": { "port": 8080 }, "tags": ["a", "b"] });
+//! let Document(tree) = serde_json::from_value(json).unwrap();
+//! assert_eq!(*tree["server"]["port"].value(), Value::Int(8080));
+//! assert_eq!(*tree["tags"]["1"].value(), Value::String("b".to_owned()));
+//!
+//! let json = serde_json::to_value(&Document(tree)).unwrap();
+//! assert_eq!(json["tags"], json!({ "0": "a", "1": "b" }));
+//! ```
+
+use super::{Value, ValueVisitor};
+use crate::Tree;
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+use serde::{
+    de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor},
+    ser::{self, Serialize, SerializeMap, Serializer},
+};
+
+pub fn serialize<S: Serializer>(
+    tree: &Tree<String, Value>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    Nested(tree).serialize(serializer)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Tree<String, Value>, D::Error> {
+    NestedSeed.deserialize(deserializer)
+}
+
+struct Nested<'a>(&'a Tree<String, Value>);
+
+impl Serialize for Nested<'_> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let tree = self.0;
+        if tree.is_childless() {
+            return tree.value.serialize(serializer);
+        }
+        if !tree.value.is_null() {
+            return Err(ser::Error::custom(
+                "a node with children can only hold a null value",
+            ));
+        }
+
+        let mut children: Vec<_> = tree.iter_single().collect();
+        if children.iter().all(|(key, _)| is_index(key)) {
+            // without leading zeros, shorter numbers are smaller
+            children.sort_by_key(|(key, _)| (key.len(), *key));
+        }
+        let mut map = serializer.serialize_map(Some(children.len()))?;
+        for (key, child) in children {
+            map.serialize_entry(key, &Nested(child))?;
+        }
+        map.end()
+    }
+}
+
+/// Checks if the key is an index as written by `visit_seq`
+fn is_index(key: &str) -> bool {
+    match key.as_bytes() {
+        [b'0'] => true,
+        [b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+        _ => false,
+    }
+}
+
+struct NestedSeed;
+
+impl<'de> DeserializeSeed<'de> for NestedSeed {
+    type Value = Tree<String, Value>;
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+/// Reads scalars through [`ValueVisitor`] and builds nodes out of the rest
+impl<'de> Visitor<'de> for NestedSeed {
+    type Value = Tree<String, Value>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a value, a sequence or a map")
+    }
+
+    fn visit_bool<E: de::Error>(self, b: bool) -> Result<Self::Value, E> {
+        ValueVisitor.visit_bool(b).map(Tree::new)
+    }
+
+    fn visit_i64<E: de::Error>(self, i: i64) -> Result<Self::Value, E> {
+        ValueVisitor.visit_i64(i).map(Tree::new)
+    }
+
+    fn visit_u64<E: de::Error>(self, u: u64) -> Result<Self::Value, E> {
+        ValueVisitor.visit_u64(u).map(Tree::new)
+    }
+
+    fn visit_f64<E: de::Error>(self, x: f64) -> Result<Self::Value, E> {
+        ValueVisitor.visit_f64(x).map(Tree::new)
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        ValueVisitor.visit_str(s).map(Tree::new)
+    }
+
+    fn visit_string<E: de::Error>(self, s: String) -> Result<Self::Value, E> {
+        ValueVisitor.visit_string(s).map(Tree::new)
+    }
+
+    fn visit_bytes<E: de::Error>(self, b: &[u8]) -> Result<Self::Value, E> {
+        ValueVisitor.visit_bytes(b).map(Tree::new)
+    }
+
+    fn visit_byte_buf<E: de::Error>(
+        self,
+        b: Vec<u8>,
+    ) -> Result<Self::Value, E> {
+        ValueVisitor.visit_byte_buf(b).map(Tree::new)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Tree::new(Value::Null))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Tree::new(Value::Null))
+    }
+
+    fn visit_some<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> Result<Self::Value, A::Error> {
+        let mut tree = Tree::new(Value::Null);
+        let mut index = 0usize;
+        while let Some(child) = seq.next_element_seed(NestedSeed)? {
+            tree.children.insert(index.to_string(), child);
+            index += 1;
+        }
+        Ok(tree)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> Result<Self::Value, A::Error> {
+        let mut tree = Tree::new(Value::Null);
+        while let Some(key) = map.next_key::<String>()? {
+            if tree.children.contains_key(&key) {
+                return Err(de::Error::custom(format_args!(
+                    "duplicate key `{}`",
+                    key
+                )));
+            }
+            let child = map.next_value_seed(NestedSeed)?;
+            tree.children.insert(key, child);
+        }
+        Ok(tree)
+    }
+}
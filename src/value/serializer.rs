@@ -0,0 +1,576 @@
+use super::{Error, Value};
+use crate::Tree;
+use alloc::{
+    borrow::ToOwned,
+    string::{String, ToString},
+};
+use core::convert::TryFrom;
+use serde::ser::{self, Impossible, Serialize};
+
+/// Serializes a value into a tree, laid out like [`nested`](super::nested)
+/// data
+///
+/// Structs and maps become nodes holding [`Value::Null`], with a child for
+/// each field or entry, and sequences become nodes keyed by the index of each
+/// element. Enum variants with data are nodes with a single child, keyed by
+/// the name of the variant, while unit variants are just their name.
+///
+/// ```
+/// use serde::Serialize;
+/// use tagged_tree::value::Value;
+///
+/// #[derive(Serialize)]
+/// struct Config {
+///     name: &'static str,
+///     ports: Vec<u16>,
+/// }
+///
+/// let config = Config { name: "app", ports: vec![80, 443] };
+/// let tree = tagged_tree::to_tree(&config).unwrap();
+/// assert_eq!(*tree["name"].value(), Value::String("app".to_owned()));
+/// assert_eq!(*tree["ports"]["1"].value(), Value::Int(443));
+/// ```
+pub fn to_tree<T: Serialize + ?Sized>(
+    value: &T,
+) -> core::result::Result<Tree<String, Value>, Error> {
+    value.serialize(Serializer)
+}
+
+type Result<T> = core::result::Result<T, Error>;
+
+struct Serializer;
+
+fn leaf<V: Into<Value>>(value: V) -> Result<Tree<String, Value>> {
+    Ok(Tree::new(value.into()))
+}
+
+fn int<I>(i: I) -> Result<Tree<String, Value>>
+where
+    i64: TryFrom<I>,
+{
+    i64::try_from(i)
+        .map(|i| Tree::new(Value::Int(i)))
+        .map_err(|_| Error::new("integer out of range"))
+}
+
+/// A node holding a single child keyed by the name of the variant
+fn variant(name: &str, content: Tree<String, Value>) -> Tree<String, Value> {
+    let mut tree = Tree::new(Value::Null);
+    tree.children.insert(name.to_owned(), content);
+    tree
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Tree<String, Value>;
+    type Error = Error;
+    type SerializeSeq = SerializeSeq;
+    type SerializeTuple = SerializeSeq;
+    type SerializeTupleStruct = SerializeSeq;
+    type SerializeTupleVariant = SerializeVariant<SerializeSeq>;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeVariant<SerializeMap>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
+        leaf(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
+        int(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
+        int(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
+        int(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
+        int(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
+        int(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
+        int(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
+        int(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
+        int(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
+        int(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
+        int(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
+        leaf(Value::Float(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
+        leaf(Value::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+        leaf(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok> {
+        leaf(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
+        leaf(v)
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        leaf(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(
+        self,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        leaf(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Self::Ok> {
+        leaf(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok> {
+        leaf(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        Ok(variant(name, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(SerializeSeq {
+            tree: Tree::new(Value::Null),
+            index: 0,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Ok(SerializeVariant {
+            name,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(SerializeMap {
+            tree: Tree::new(Value::Null),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Ok(SerializeVariant {
+            name,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+struct SerializeSeq {
+    tree: Tree<String, Value>,
+    index: usize,
+}
+
+impl SerializeSeq {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let child = value.serialize(Serializer)?;
+        self.tree.children.insert(self.index.to_string(), child);
+        self.index += 1;
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for SerializeSeq {
+    type Ok = Tree<String, Value>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(self.tree)
+    }
+}
+
+impl ser::SerializeTuple for SerializeSeq {
+    type Ok = Tree<String, Value>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(self.tree)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeSeq {
+    type Ok = Tree<String, Value>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(self.tree)
+    }
+}
+
+struct SerializeMap {
+    tree: Tree<String, Value>,
+    key: Option<String>,
+}
+
+impl SerializeMap {
+    fn insert<T: Serialize + ?Sized>(
+        &mut self,
+        key: String,
+        value: &T,
+    ) -> Result<()> {
+        let child = value.serialize(Serializer)?;
+        self.tree.children.insert(key, child);
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Tree<String, Value>;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<()> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error::new("map value without a key"))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(self.tree)
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = Tree<String, Value>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.insert(key.to_owned(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(self.tree)
+    }
+}
+
+struct SerializeVariant<S> {
+    name: &'static str,
+    inner: S,
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeSeq> {
+    type Ok = Tree<String, Value>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<()> {
+        self.inner.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(variant(self.name, self.inner.tree))
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<SerializeMap> {
+    type Ok = Tree<String, Value>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.inner.insert(key.to_owned(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(variant(self.name, self.inner.tree))
+    }
+}
+
+/// Writes map keys as strings, the only kind of key a tree of values has
+struct KeySerializer;
+
+fn key_error() -> Error {
+    Error::new("map keys must be strings, numbers, booleans or unit variants")
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = Error;
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<String> {
+        Err(key_error())
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<String> {
+        Err(key_error())
+    }
+
+    fn serialize_char(self, v: char) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String> {
+        Ok(v.to_owned())
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<String> {
+        Err(key_error())
+    }
+
+    fn serialize_none(self) -> Result<String> {
+        Err(key_error())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _: &T) -> Result<String> {
+        Err(key_error())
+    }
+
+    fn serialize_unit(self) -> Result<String> {
+        Err(key_error())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<String> {
+        Err(key_error())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<String> {
+        Ok(variant.to_owned())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<String> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<String> {
+        Err(key_error())
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(key_error())
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple> {
+        Err(key_error())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(key_error())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(key_error())
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(key_error())
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct> {
+        Err(key_error())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(key_error())
+    }
+}
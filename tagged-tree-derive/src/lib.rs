@@ -0,0 +1,139 @@
+//! Derives `IntoTree` and `FromTree` from the `tagged-tree` crate for structs
+//! with named fields. Use them through the `derive` feature of that crate
+//! rather than depending on this one directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Fields, Generics, Ident,
+    Path,
+};
+
+#[proc_macro_derive(IntoTree)]
+pub fn derive_into_tree(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input, into_tree)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(FromTree)]
+pub fn derive_from_tree(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input, from_tree)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+fn expand(
+    input: &DeriveInput,
+    body: fn(&DeriveInput, &[(Ident, String)]) -> TokenStream2,
+) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields
+                .named
+                .iter()
+                .map(|field| {
+                    let ident = field.ident.clone().expect("fields are named");
+                    let name = ident.to_string();
+                    let name = name.trim_start_matches("r#").to_owned();
+                    (ident, name)
+                })
+                .collect::<Vec<_>>(),
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "only structs with named fields can be converted to trees",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "only structs can be converted to trees",
+            ))
+        }
+    };
+    Ok(body(input, &fields))
+}
+
+/// Requires the trait from every type parameter
+fn bounded(generics: &Generics, bound: &Path) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    generics
+}
+
+fn into_tree(input: &DeriveInput, fields: &[(Ident, String)]) -> TokenStream2 {
+    let ident = &input.ident;
+    let generics = bounded(
+        &input.generics,
+        &parse_quote!(::tagged_tree::value::IntoTree),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let children = fields.iter().map(|(field, name)| {
+        quote! {
+            tree.entry(#name).or_insert_tree(
+                ::tagged_tree::value::IntoTree::into_tree(self.#field),
+            );
+        }
+    });
+
+    quote! {
+        impl #impl_generics ::tagged_tree::value::IntoTree
+            for #ident #ty_generics #where_clause
+        {
+            fn into_tree(
+                self,
+            ) -> ::tagged_tree::Tree<&'static str, ::tagged_tree::value::Value>
+            {
+                let mut tree =
+                    ::tagged_tree::Tree::new(::tagged_tree::value::Value::Null);
+                #(#children)*
+                tree
+            }
+        }
+    }
+}
+
+fn from_tree(input: &DeriveInput, fields: &[(Ident, String)]) -> TokenStream2 {
+    let ident = &input.ident;
+    let generics = bounded(
+        &input.generics,
+        &parse_quote!(::tagged_tree::value::FromTree),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let fields = fields.iter().map(|(field, name)| {
+        quote! {
+            #field: match tree.get_child(&#name) {
+                ::core::option::Option::Some(child) => {
+                    ::tagged_tree::value::FromTree::from_tree(child)
+                        .map_err(|e| e.in_field(#name))?
+                }
+                ::core::option::Option::None => {
+                    ::tagged_tree::value::FromTree::from_missing(#name)?
+                }
+            },
+        }
+    });
+
+    quote! {
+        impl #impl_generics ::tagged_tree::value::FromTree
+            for #ident #ty_generics #where_clause
+        {
+            fn from_tree(
+                tree: &::tagged_tree::Tree<
+                    &'static str,
+                    ::tagged_tree::value::Value,
+                >,
+            ) -> ::core::result::Result<Self, ::tagged_tree::value::FromTreeError>
+            {
+                ::core::result::Result::Ok(#ident { #(#fields)* })
+            }
+        }
+    }
+}
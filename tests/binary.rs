@@ -0,0 +1,153 @@
+#![cfg(feature = "std")]
+
+use tagged_tree::{
+    binary::{ArchivedTree, Decode, Encode, MAGIC},
+    tree, Tree,
+};
+
+type TestSubject = Tree<String, i64>;
+type Archived<'a> = ArchivedTree<'a, String, i64>;
+
+fn key(key: &str) -> String {
+    key.to_owned()
+}
+
+#[test]
+fn round_trip() {
+    let tree: TestSubject = tree! { 0;
+        key("a") => (-1) { key("b") => 2, key("c") => (-3) },
+        key("d") => 4,
+    };
+    let bytes = tree.to_binary().unwrap();
+    assert!(bytes.starts_with(MAGIC));
+    assert_eq!(Archived::new(&bytes).unwrap().to_tree(), tree);
+}
+
+#[test]
+fn reads_in_place() {
+    let tree: TestSubject = tree! { 0;
+        key("a") => (-1) { key("b") => 2, key("c") => (-3) },
+        key("d") => 4,
+    };
+    let bytes = tree.to_binary().unwrap();
+    let archived = Archived::new(&bytes).unwrap();
+
+    assert_eq!(archived.value(), 0);
+    assert_eq!(archived.child_count(), 2);
+    assert_eq!(archived.get_child("a").unwrap().value(), -1);
+    assert_eq!(archived.get_path(["a", "c"]).unwrap().value(), -3);
+    assert!(archived.get_path(["a", "d"]).is_none());
+    assert!(archived.get_path(["d"]).unwrap().is_childless());
+
+    let keys: Vec<String> = archived.children().map(|(key, _)| key).collect();
+    assert_eq!(keys, ["a", "d"]);
+}
+
+#[test]
+fn zero_copy_keys() {
+    let tree: TestSubject = tree! { 0;
+        key("a") => (-1) { key("b") => 2, key("c") => (-3) },
+        key("d") => 4,
+    };
+    let bytes = tree.to_binary().unwrap();
+    let archived = ArchivedTree::<&str, i64>::new(&bytes).unwrap();
+    let a = archived.get_child("a").unwrap();
+    let keys: Vec<&str> = a.children().rev().map(|(key, _)| key).collect();
+    assert_eq!(keys, ["c", "b"]);
+}
+
+#[test]
+fn integer_keys_keep_their_order() {
+    let mut tree = Tree::new(());
+    for key in &[-300i32, -1, 0, 7, 1 << 20] {
+        tree.add_child(*key, ());
+    }
+    let bytes = tree.to_binary().unwrap();
+    let archived = ArchivedTree::<i32, ()>::new(&bytes).unwrap();
+
+    let keys: Vec<i32> = archived.children().map(|(key, _)| key).collect();
+    assert_eq!(keys, [-300, -1, 0, 7, 1 << 20]);
+    assert!(archived.get_child(&-1i32).is_some());
+    assert!(archived.get_child(&1i32).is_none());
+}
+
+#[test]
+fn encodings_round_trip() {
+    fn check<T>(value: T)
+    where
+        T: Encode + for<'a> Decode<'a> + PartialEq + std::fmt::Debug,
+    {
+        let mut bytes = Vec::new();
+        value.encode(&mut bytes);
+        assert_eq!(T::decode(&bytes).unwrap(), value);
+    }
+
+    check(i8::MIN);
+    check(-5i64);
+    check(u128::MAX);
+    check(usize::MAX);
+    check(true);
+    check('é');
+    check("text".to_owned());
+    check(vec![1u8, 2, 3]);
+}
+
+#[test]
+fn rejects_malformed_bytes() {
+    let tree: TestSubject = tree! { 0;
+        key("a") => (-1) { key("b") => 2, key("c") => (-3) },
+        key("d") => 4,
+    };
+    let bytes = tree.to_binary().unwrap();
+
+    assert!(Archived::new(&bytes[1..]).is_err());
+    assert!(Archived::new(&bytes[..bytes.len() - 1]).is_err());
+
+    let mut extra = bytes.clone();
+    extra.push(0);
+    assert!(Archived::new(&extra).is_err());
+
+    // the value of the root is the last thing before its child count
+    let mut value = bytes.clone();
+    value[MAGIC.len() + 4] = 7;
+    assert!(Archived::new(&value).is_err());
+
+    for index in MAGIC.len()..bytes.len() {
+        let mut corrupt = bytes.clone();
+        corrupt[index] ^= 0xff;
+        if let Ok(archived) = Archived::new(&corrupt) {
+            // corrupting a value can produce another valid tree
+            archived.to_tree();
+        }
+    }
+}
+
+#[test]
+fn trusted_skips_validation() {
+    let tree: TestSubject = tree! { 0;
+        key("a") => (-1) { key("b") => 2, key("c") => (-3) },
+        key("d") => 4,
+    };
+    let bytes = tree.to_binary().unwrap();
+    let archived = Archived::trusted(&bytes).unwrap();
+    assert_eq!(archived.get_path(["a", "b"]).unwrap().value(), 2);
+
+    // the length of the root is still checked
+    let mut long = bytes.clone();
+    long[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(Archived::trusted(&long).is_err());
+    let mut short = bytes;
+    short[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&4u32.to_le_bytes());
+    assert!(Archived::trusted(&short).is_err());
+}
+
+#[test]
+fn write_binary() {
+    let tree: TestSubject = tree! { 0;
+        key("a") => (-1) { key("b") => 2, key("c") => (-3) },
+        key("d") => 4,
+    };
+    let mut out = Vec::new();
+    tree.write_binary(&mut out).unwrap();
+    assert_eq!(out, tree.to_binary().unwrap());
+}
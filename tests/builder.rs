@@ -0,0 +1,84 @@
+use tagged_tree::{Tree, TreeBuilder};
+
+#[test]
+fn collects_paths() {
+    let tree: Tree<u8, i32> = vec![(vec![1, 2], 12), (vec![3], 3), (vec![], 7)]
+        .into_iter()
+        .collect();
+
+    assert_eq!(*tree.value(), 7);
+    assert_eq!(*tree[&1].value(), 0);
+    assert_eq!(*tree[&1][&2].value(), 12);
+    assert_eq!(*tree[&3].value(), 3);
+    assert_eq!(tree.children().len(), 2);
+}
+
+#[test]
+fn later_paths_win_when_collecting() {
+    let tree: Tree<u8, i32> =
+        vec![(vec![1], 1), (vec![1], 2)].into_iter().collect();
+    assert_eq!(*tree[&1].value(), 2);
+}
+
+#[test]
+fn intermediate_nodes_can_be_given_values_later() {
+    let tree =
+        Tree::try_from_paths(vec![(vec![1, 2], 12), (vec![1], 1)]).unwrap();
+    assert_eq!(*tree[&1].value(), 1);
+    assert_eq!(*tree[&1][&2].value(), 12);
+}
+
+#[test]
+fn reports_duplicate_paths() {
+    let error = Tree::try_from_paths(vec![
+        (vec![1, 2], 12),
+        (vec![3], 3),
+        (vec![1, 2], 21),
+    ])
+    .unwrap_err();
+    assert_eq!(error.path(), &[1, 2]);
+    assert_eq!(error.to_string(), "duplicate path [1, 2]");
+}
+
+#[test]
+fn fills_intermediate_nodes_with_custom_default() {
+    let mut count = 0;
+    let mut builder = TreeBuilder::with_default(|| {
+        count += 1;
+        -count
+    });
+    builder.insert(vec!['a', 'b', 'c'], 3).unwrap();
+    assert_eq!(builder.replace(vec!['a', 'b', 'c'], 4), Some(3));
+    let tree = builder.build();
+
+    assert_eq!(*tree.value(), -1);
+    assert_eq!(*tree[&'a'].value(), -2);
+    assert_eq!(*tree[&'a'][&'b'].value(), -3);
+    assert_eq!(*tree[&'a'][&'b'][&'c'].value(), 4);
+}
+
+#[test]
+fn builds_from_delimited_strings() {
+    let tree: Tree<String, ()> =
+        Tree::from_delimited_strings(vec!["a/b/c", "/a/d/", "e"], "/").unwrap();
+
+    let mut keys: Vec<_> = tree
+        .iter_paths()
+        .map(|(keys, _)| {
+            keys.iter()
+                .map(|k| k.as_str())
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .collect();
+    keys.sort();
+    assert_eq!(keys, ["a", "a/b", "a/b/c", "a/d", "e"]);
+}
+
+#[test]
+fn delimited_strings_report_duplicates() {
+    let error =
+        Tree::<String, ()>::from_delimited_strings(["a::b", "a::b"], "::")
+            .unwrap_err();
+    assert_eq!(error.into_path(), ["a", "b"]);
+}
@@ -0,0 +1,151 @@
+#![cfg(feature = "serde")]
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tagged_tree::Tree;
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[serde(bound(
+    serialize = "V: Serialize",
+    deserialize = "V: Deserialize<'de>"
+))]
+struct Wrapper<V> {
+    #[serde(with = "tagged_tree::compact")]
+    tree: Tree<String, V>,
+}
+
+fn key(key: &str) -> String {
+    key.to_owned()
+}
+
+#[test]
+fn serializes_values_under_reserved_key() {
+    let mut tree = Tree::new(0);
+    tree.entry(key("a")).or_insert(1).add_child(key("b"), 2);
+    tree.add_child(key("c"), 3);
+
+    let value = serde_json::to_value(&Wrapper { tree }).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "tree": {
+                "$value": 0,
+                "a": { "$value": 1, "b": { "$value": 2 } },
+                "c": { "$value": 3 },
+            }
+        })
+    );
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+struct Keys {
+    #[serde(with = "tagged_tree::compact::keys")]
+    tree: Tree<String, ()>,
+}
+
+#[test]
+fn keys_leave_out_values() {
+    let mut tree = Tree::new(());
+    tree.entry(key("a")).or_insert(()).add_child(key("b"), ());
+    tree.add_child(key("c"), ());
+
+    let keys = Keys { tree };
+    let value = serde_json::to_value(&keys).unwrap();
+    assert_eq!(value, json!({ "tree": { "a": { "b": {} }, "c": {} } }));
+    assert_eq!(serde_json::from_value::<Keys>(value).unwrap(), keys);
+}
+
+#[test]
+fn unit_values_are_written_unless_asked() {
+    let mut tree = Tree::new(());
+    tree.add_child(key("a"), ());
+
+    let wrapper = Wrapper { tree };
+    let value = serde_json::to_value(&wrapper).unwrap();
+    assert_eq!(
+        value,
+        json!({ "tree": { "$value": null, "a": { "$value": null } } })
+    );
+    // which can be read back either way
+    assert_eq!(
+        serde_json::from_value::<Wrapper<()>>(value.clone()).unwrap(),
+        wrapper
+    );
+    assert_eq!(
+        serde_json::from_value::<Keys>(value).unwrap().tree,
+        wrapper.tree
+    );
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+struct Marker;
+
+#[test]
+fn zero_sized_values_are_written() {
+    let wrapper = Wrapper {
+        tree: Tree::new(Marker),
+    };
+    let value = serde_json::to_value(&wrapper).unwrap();
+    assert_eq!(value, json!({ "tree": { "$value": null } }));
+    assert_eq!(
+        serde_json::from_value::<Wrapper<Marker>>(value).unwrap(),
+        wrapper
+    );
+}
+
+#[test]
+fn round_trip() {
+    let mut tree = Tree::new(key("root"));
+    tree.entry(key("a"))
+        .or_insert(key("x"))
+        .add_child(key("b"), key("y"));
+
+    let wrapper = Wrapper { tree };
+    let json = serde_json::to_string(&wrapper).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Wrapper<String>>(&json).unwrap(),
+        wrapper
+    );
+}
+
+#[test]
+fn optional_values_may_be_left_out() {
+    let wrapper: Wrapper<Option<u32>> = serde_json::from_value(json!({
+        "tree": { "a": { "$value": 1 }, "b": {} }
+    }))
+    .unwrap();
+    assert_eq!(*wrapper.tree.value(), None);
+    assert_eq!(*wrapper.tree[&key("a")].value(), Some(1));
+    assert_eq!(*wrapper.tree[&key("b")].value(), None);
+}
+
+#[test]
+fn reports_missing_values() {
+    let err = serde_json::from_value::<Wrapper<u32>>(json!({
+        "tree": { "$value": 0, "a": {} }
+    }))
+    .unwrap_err();
+    assert!(err.to_string().contains("missing field `$value`"));
+}
+
+#[test]
+fn rejects_reserved_keys() {
+    let mut tree = Tree::new(0);
+    tree.add_child(key("$value"), 1);
+    assert!(serde_json::to_string(&Wrapper { tree }).is_err());
+}
+
+#[test]
+fn reports_duplicate_keys() {
+    #[derive(Deserialize, Debug)]
+    struct Numbers {
+        #[serde(with = "tagged_tree::compact::keys")]
+        #[allow(dead_code)]
+        tree: Tree<u32, ()>,
+    }
+
+    let err =
+        serde_json::from_str::<Numbers>(r#"{ "tree": { "1": {}, "01": {} } }"#)
+            .unwrap_err();
+    assert!(err.to_string().contains("duplicate key `01`"), "{}", err);
+}
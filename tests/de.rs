@@ -0,0 +1,190 @@
+#![cfg(feature = "serde")]
+
+use serde::de::DeserializeSeed;
+use std::convert::Infallible;
+use tagged_tree::{
+    de::{NodeVisitor, TreeSeed, VisitorSeed},
+    tree, Tree,
+};
+
+type TestSubject = Tree<String, u32>;
+
+fn key(key: &str) -> String {
+    key.to_owned()
+}
+
+fn linear_json(depth: usize) -> String {
+    let mut json = String::new();
+    for _ in 0..depth {
+        json.push_str(r#"{"value":0,"children":{"a":"#);
+    }
+    json.push_str(r#"{"value":0,"children":{}}"#);
+    for _ in 0..depth {
+        json.push_str("}}");
+    }
+    json
+}
+
+#[test]
+fn round_trip() {
+    let tree: TestSubject = tree! { 0;
+        key("a") => 1 { key("b") => 2 },
+        key("c") => 3,
+    };
+    let json = serde_json::to_string(&tree).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Tree<String, u32>>(&json).unwrap(),
+        tree
+    );
+}
+
+#[test]
+fn accepts_fields_in_any_order() {
+    let json = r#"{"children":{"a":{"children":{},"value":1}},"value":0}"#;
+    let tree: Tree<String, u32> = serde_json::from_str(json).unwrap();
+    assert_eq!(*tree.value(), 0);
+    assert_eq!(*tree[&"a".to_owned()].value(), 1);
+}
+
+#[test]
+fn accepts_sequences() {
+    let tree: Tree<String, u32> =
+        serde_json::from_str(r#"[0, {"a": [1, {}]}]"#).unwrap();
+    assert_eq!(*tree[&"a".to_owned()].value(), 1);
+}
+
+#[test]
+fn reports_missing_fields() {
+    let err = serde_json::from_str::<Tree<String, u32>>(r#"{"children":{}}"#)
+        .unwrap_err();
+    assert!(err.to_string().contains("missing field `value`"));
+}
+
+#[test]
+fn deserialize_uses_the_default_max_depth() {
+    let deep = || {
+        let mut json = serde_json::json!({ "value": 0, "children": {} });
+        for _ in 0..600 {
+            json = serde_json::json!({ "value": 0, "children": { "a": json } });
+        }
+        json
+    };
+    let err = serde_json::from_value::<Tree<String, u32>>(deep()).unwrap_err();
+    assert!(err.to_string().contains("nested deeper than 512 levels"));
+
+    let tree = TreeSeed::<String, u32>::new()
+        .max_depth(usize::MAX)
+        .deserialize(deep())
+        .unwrap();
+    assert_eq!(tree.iter_paths().count(), 600);
+}
+
+#[test]
+fn enforces_max_depth() {
+    let json = linear_json(3);
+    let seed = || TreeSeed::<String, u32>::new();
+
+    let tree = seed()
+        .max_depth(3)
+        .deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .unwrap();
+    assert_eq!(tree.iter_paths().count(), 3);
+
+    let err = seed()
+        .max_depth(2)
+        .deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .unwrap_err();
+    assert!(err.to_string().contains("nested deeper than 2 levels"));
+}
+
+#[derive(Default)]
+struct Events(Vec<String>);
+
+impl NodeVisitor<String, u32> for Events {
+    type Error = Infallible;
+
+    fn enter(
+        &mut self,
+        key: Option<String>,
+        depth: usize,
+    ) -> Result<(), Infallible> {
+        self.0.push(format!("enter {:?} {}", key, depth));
+        Ok(())
+    }
+
+    fn value(&mut self, value: u32) -> Result<(), Infallible> {
+        self.0.push(format!("value {}", value));
+        Ok(())
+    }
+
+    fn leave(&mut self) -> Result<(), Infallible> {
+        self.0.push("leave".to_owned());
+        Ok(())
+    }
+}
+
+#[test]
+fn streams_nodes() {
+    let tree: TestSubject = tree! { 0;
+        key("a") => 1 { key("b") => 2 },
+        key("c") => 3,
+    };
+    let json = serde_json::to_string(&tree).unwrap();
+    let mut events = Events::default();
+    VisitorSeed::new(&mut events)
+        .deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .unwrap();
+
+    assert_eq!(
+        events.0,
+        [
+            "enter None 0",
+            "value 0",
+            "enter Some(\"a\") 1",
+            "value 1",
+            "enter Some(\"b\") 2",
+            "value 2",
+            "leave",
+            "leave",
+            "enter Some(\"c\") 1",
+            "value 3",
+            "leave",
+            "leave",
+        ]
+    );
+}
+
+struct StopAt(u32);
+
+impl NodeVisitor<String, u32> for StopAt {
+    type Error = String;
+
+    fn enter(&mut self, _: Option<String>, _: usize) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn value(&mut self, value: u32) -> Result<(), String> {
+        if value == self.0 {
+            Err(format!("found {}", value))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn leave(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[test]
+fn visitor_errors_abort() {
+    let tree: TestSubject = tree! { 0;
+        key("a") => 1 { key("b") => 2 },
+        key("c") => 3,
+    };
+    let json = serde_json::to_string(&tree).unwrap();
+    let err = VisitorSeed::new(&mut StopAt(2))
+        .deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .unwrap_err();
+    assert!(err.to_string().contains("found 2"));
+}
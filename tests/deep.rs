@@ -0,0 +1,69 @@
+use std::{
+    cmp::Ordering,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+use tagged_tree::Tree;
+
+const DEPTH: usize = 100_000;
+
+fn linear_tree(leaf: usize) -> Tree<usize, usize> {
+    let mut tree = Tree::new(0);
+    let mut node = &mut tree;
+    for depth in 1..DEPTH {
+        node = node.entry(0).or_insert(depth);
+    }
+    node.add_child(0, leaf);
+    tree
+}
+
+fn hash(tree: &Tree<usize, usize>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    tree.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn drops_deep_trees() {
+    drop(linear_tree(0));
+}
+
+#[test]
+fn clones_deep_trees() {
+    let tree = linear_tree(0);
+    let clone = tree.clone();
+    assert!(clone == tree);
+}
+
+#[test]
+fn compares_deep_trees() {
+    let tree = linear_tree(0);
+    let other = linear_tree(1);
+
+    assert!(tree == linear_tree(0));
+    assert!(tree != other);
+    assert_eq!(tree.cmp(&other), Ordering::Less);
+    assert_eq!(other.partial_cmp(&tree), Some(Ordering::Greater));
+    assert_eq!(tree.cmp(&linear_tree(0)), Ordering::Equal);
+}
+
+#[test]
+fn hashes_deep_trees() {
+    assert_eq!(hash(&linear_tree(0)), hash(&linear_tree(0)));
+    assert_ne!(hash(&linear_tree(0)), hash(&linear_tree(1)));
+}
+
+#[test]
+fn orders_like_the_children() {
+    let mut small = Tree::new(0);
+    small.add_child(1, 5);
+    let mut large = Tree::new(0);
+    large.add_child(2, 0);
+    let mut longer = small.clone();
+    longer.add_child(3, 0);
+
+    assert!(small < large);
+    assert!(small < longer);
+    assert!(Tree::<usize, usize>::new(0) < small);
+    assert!(Tree::<usize, usize>::new(1) > large);
+}
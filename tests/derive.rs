@@ -0,0 +1,135 @@
+#![cfg(feature = "derive")]
+
+use tagged_tree::{
+    value::{FromTree, IntoTree, Value},
+    Tree,
+};
+
+#[derive(IntoTree, FromTree, Debug, Clone, PartialEq)]
+struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(IntoTree, FromTree, Debug, Clone, PartialEq)]
+struct Settings {
+    name: String,
+    verbose: bool,
+    ratio: f64,
+    server: Server,
+    backup: Option<Server>,
+    r#type: Option<i32>,
+}
+
+fn settings() -> Settings {
+    Settings {
+        name: "app".to_owned(),
+        verbose: true,
+        ratio: 0.5,
+        server: Server {
+            host: "localhost".to_owned(),
+            port: 8080,
+        },
+        backup: None,
+        r#type: Some(3),
+    }
+}
+
+#[test]
+fn fields_become_children() {
+    let tree = settings().into_tree();
+
+    assert_eq!(*tree.value(), Value::Null);
+    assert_eq!(*tree[&"name"].value(), Value::String("app".to_owned()));
+    assert_eq!(*tree[&"verbose"].value(), Value::Bool(true));
+    assert_eq!(*tree[&"ratio"].value(), Value::Float(0.5));
+    assert_eq!(*tree[&"server"][&"port"].value(), Value::Int(8080));
+    assert_eq!(*tree[&"backup"].value(), Value::Null);
+    assert_eq!(*tree[&"type"].value(), Value::Int(3));
+}
+
+#[test]
+fn round_trips() {
+    let tree = settings().into_tree();
+    assert_eq!(Settings::from_tree(&tree).unwrap(), settings());
+
+    let mut with_backup = settings();
+    with_backup.backup = Some(with_backup.server.clone());
+    let tree = with_backup.clone().into_tree();
+    assert_eq!(Settings::from_tree(&tree).unwrap(), with_backup);
+}
+
+#[test]
+fn missing_options_are_none() {
+    let mut tree = settings().into_tree();
+    tree.remove(&"backup");
+    tree.remove(&"type");
+
+    let read = Settings::from_tree(&tree).unwrap();
+    assert_eq!(read.backup, None);
+    assert_eq!(read.r#type, None);
+}
+
+#[test]
+fn errors_report_the_path() {
+    let mut tree = settings().into_tree();
+    tree.get_child_mut(&"server")
+        .unwrap()
+        .add_child("port", Value::Int(-1));
+    let error = Settings::from_tree(&tree).unwrap_err();
+    assert_eq!(error.path(), ["server", "port"]);
+    assert_eq!(error.to_string(), "integer out of range at `server.port`");
+
+    let mut tree = settings().into_tree();
+    tree.get_child_mut(&"server").unwrap().remove(&"host");
+    let error = Settings::from_tree(&tree).unwrap_err();
+    assert_eq!(error.to_string(), "missing field at `server.host`");
+}
+
+#[test]
+fn trees_can_be_merged_generically() {
+    let mut base = settings().into_tree();
+    let mut overrides = Tree::new(Value::Null);
+    overrides
+        .entry("server")
+        .or_insert(Value::Null)
+        .add_child("port", Value::Int(9090));
+
+    for (path, node) in overrides.iter_paths() {
+        if node.is_childless() {
+            let target = path
+                .iter()
+                .try_fold(&mut base, |node, key| node.get_child_mut(*key))
+                .unwrap();
+            target.set_value(node.value().clone());
+        }
+    }
+
+    let merged = Settings::from_tree(&base).unwrap();
+    assert_eq!(merged.server.port, 9090);
+}
+
+#[derive(IntoTree, FromTree, Debug, PartialEq)]
+struct Wrapper<T> {
+    inner: T,
+}
+
+#[test]
+fn generic_structs() {
+    let tree = Wrapper { inner: 7u8 }.into_tree();
+    assert_eq!(
+        Wrapper::<u8>::from_tree(&tree).unwrap(),
+        Wrapper { inner: 7 }
+    );
+}
+
+#[test]
+fn wide_integers_are_range_checked() {
+    assert_eq!(u64::from_tree(&7i64.into_tree()).unwrap(), 7);
+    assert_eq!(usize::from_tree(&7i64.into_tree()).unwrap(), 7);
+    assert_eq!(isize::from_tree(&(-7isize).into_tree()).unwrap(), -7);
+
+    let err = u64::from_tree(&(-1i64).into_tree()).unwrap_err();
+    assert!(err.to_string().contains("integer out of range"));
+    assert!(usize::from_tree(&Tree::new(Value::Int(-1))).is_err());
+}
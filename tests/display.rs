@@ -0,0 +1,86 @@
+use tagged_tree::{display::Style, tree, Tree};
+
+type TestSubject = Tree<&'static str, usize>;
+
+#[test]
+fn display_impl() {
+    let tree: TestSubject = tree! { 0;
+        "a" => 1 { "b" => 2 { "c" => 3 }, "d" => 4 },
+        "e" => 5,
+    };
+    let expected = "\
+0
+├── a: 1
+│   ├── b: 2
+│   │   └── c: 3
+│   └── d: 4
+└── e: 5
+";
+    assert_eq!(tree.to_string(), expected);
+}
+
+#[test]
+fn custom_labels_in_ascii() {
+    let tree: TestSubject = tree! { 0;
+        "a" => 1 { "b" => 2 { "c" => 3 }, "d" => 4 },
+        "e" => 5,
+    };
+    let rendered = tree
+        .display_with(|key, value| match key {
+            Some(key) => format!("{}={}", key, value),
+            None => "root".to_owned(),
+        })
+        .style(Style::Ascii)
+        .to_string();
+
+    let expected = "\
+root
+|-- a=1
+|   |-- b=2
+|   |   `-- c=3
+|   `-- d=4
+`-- e=5
+";
+    assert_eq!(rendered, expected);
+}
+
+#[test]
+fn max_depth() {
+    let tree: TestSubject = tree! { 0;
+        "a" => 1 { "b" => 2 { "c" => 3 }, "d" => 4 },
+        "e" => 5,
+    };
+    let rendered = tree
+        .display_with(|_, value| *value)
+        .max_depth(1)
+        .to_string();
+    assert_eq!(rendered, "0\n├── 1\n└── 5\n");
+
+    let rendered = tree
+        .display_with(|_, value| *value)
+        .max_depth(0)
+        .to_string();
+    assert_eq!(rendered, "0\n");
+}
+
+#[test]
+fn max_children() {
+    let mut tree = TestSubject::new(0);
+    for (value, key) in ["a", "b", "c", "d", "e"].iter().enumerate() {
+        tree.add_child(key, value + 1);
+    }
+    tree.entry("a").or_insert(1).add_child("x", 6);
+
+    let rendered = tree
+        .display_with(|_, value| *value)
+        .max_children(2)
+        .to_string();
+    let expected = "\
+0
+├── 1
+│   └── 6
+├── 2
+└── ... 3 more
+";
+    assert_eq!(rendered, expected);
+}
@@ -0,0 +1,100 @@
+use tagged_tree::{dot::DotOptions, tree, Tree};
+
+type TestSubject = Tree<&'static str, usize>;
+
+#[test]
+fn default_options() {
+    let tree: TestSubject = tree! { 0; "a" => 1 { "b" => 2 }, "c\"" => 3 };
+    let expected = r#"digraph "tree" {
+    n0 [label="0"];
+    n1 [label="1"];
+    n3 [label="3"];
+    n2 [label="2"];
+    n0 -> n1 [label="a"];
+    n1 -> n2 [label="b"];
+    n0 -> n3 [label="c\""];
+}
+"#;
+    assert_eq!(tree.to_dot(&DotOptions::new()), expected);
+}
+
+#[test]
+fn cluster_by_depth() {
+    let tree: TestSubject = tree! { 0; "a" => 1 { "b" => 2 }, "c\"" => 3 };
+    let dot = tree.to_dot(&DotOptions::new().cluster_by_depth(true));
+    let expected = r#"digraph "tree" {
+    subgraph cluster_depth_0 {
+        label="depth 0";
+        n0 [label="0"];
+    }
+    subgraph cluster_depth_1 {
+        label="depth 1";
+        n1 [label="1"];
+        n3 [label="3"];
+    }
+    subgraph cluster_depth_2 {
+        label="depth 2";
+        n2 [label="2"];
+    }
+"#;
+    assert!(dot.starts_with(expected), "{}", dot);
+}
+
+#[test]
+fn custom_node_attributes() {
+    let tree: TestSubject = tree! { 0; "a" => 1 { "b" => 2 }, "c\"" => 3 };
+    let options = DotOptions::new().name("custom").node_attributes(
+        |path: &[&&str], value: &usize| {
+            let mut attributes = vec![("shape".to_owned(), "box".to_owned())];
+            if !path.is_empty() {
+                attributes.push((
+                    "label".to_owned(),
+                    path.iter().map(|key| **key).collect::<Vec<_>>().join("/"),
+                ));
+            }
+            if *value == 2 {
+                attributes.push(("color".to_owned(), "red".to_owned()));
+            }
+            attributes
+        },
+    );
+    let dot = tree.to_dot(&options);
+
+    assert!(dot.starts_with("digraph \"custom\" {\n"));
+    assert!(dot.contains("n0 [label=\"0\", shape=\"box\"];"));
+    assert!(dot.contains("n2 [shape=\"box\", label=\"a/b\", color=\"red\"];"));
+}
+
+#[test]
+fn truncation() {
+    let tree: TestSubject = tree! { 0; "a" => 1 { "b" => 2 }, "c\"" => 3 };
+    let dot = tree.to_dot(&DotOptions::new().max_depth(1));
+    assert!(dot.contains("n0 -> n1"));
+    assert!(dot.contains("n0 -> n2"));
+    assert!(!dot.contains("n3"));
+    assert!(dot.contains("truncated"));
+
+    let dot = tree.to_dot(&DotOptions::new().max_nodes(2));
+    assert!(dot.contains("n0 -> n1 [label=\"a\"]"));
+    assert!(!dot.contains("n2"));
+    assert!(dot.contains("truncated"));
+
+    let dot = tree.to_dot(&DotOptions::new().max_nodes(4));
+    assert!(!dot.contains("truncated"));
+
+    let dot = tree.to_dot(&DotOptions::new().max_nodes(0));
+    assert!(!dot.contains("n0"));
+    assert!(dot.contains("truncated"));
+}
+
+#[test]
+fn attribute_names_are_quoted() {
+    let tree: TestSubject = tree! { 0; "a" => 1 { "b" => 2 }, "c\"" => 3 };
+    let options =
+        DotOptions::new().node_attributes(|_: &[&&str], _: &usize| {
+            vec![("x\"] n9 [label".to_owned(), "y".to_owned())]
+        });
+    let dot = tree.to_dot(&options);
+    assert!(dot.contains("n0 [label=\"0\", \"x\\\"] n9 [label\"=\"y\"];"));
+    assert!(!dot.contains("n9 [label=\"y"));
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "serde")]
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tagged_tree::{tree, Tree};
+
+fn key(key: &str) -> String {
+    key.to_owned()
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+struct Wrapper {
+    #[serde(with = "tagged_tree::flat")]
+    tree: Tree<String, usize>,
+}
+
+#[test]
+fn serializes_as_flat_map() {
+    let tree = tree! { 0;
+        key("a") => 1 { key("b") => 2, key("c/d~e") => 3 },
+        String::new() => 4,
+    };
+    let value = serde_json::to_value(&Wrapper { tree }).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "tree": {
+                "": 0,
+                "/": 4,
+                "/a": 1,
+                "/a/b": 2,
+                "/a/c~1d~0e": 3,
+            }
+        })
+    );
+}
+
+#[test]
+fn round_trip() {
+    let tree = tree! { 0;
+        key("a") => 1 { key("b") => 2, key("c/d~e") => 3 },
+        String::new() => 4,
+    };
+    let wrapper = Wrapper { tree };
+    let json = serde_json::to_string(&wrapper).unwrap();
+    assert_eq!(serde_json::from_str::<Wrapper>(&json).unwrap(), wrapper);
+}
+
+#[test]
+fn deserializes_in_any_order() {
+    let wrapper: Wrapper = serde_json::from_value(json!({
+        "tree": { "/a/b": 2, "/a": 1, "": 0 }
+    }))
+    .unwrap();
+    assert_eq!(*wrapper.tree[&"a".to_owned()][&"b".to_owned()].value(), 2);
+}
+
+#[test]
+fn numeric_keys() {
+    #[derive(Serialize, Deserialize)]
+    struct Numeric(#[serde(with = "tagged_tree::flat")] Tree<u32, bool>);
+
+    let mut tree = Tree::new(true);
+    tree.entry(10).or_insert(false).add_child(2, true);
+    let json = serde_json::to_string(&Numeric(tree.clone())).unwrap();
+    assert_eq!(json, r#"{"":true,"/10":false,"/10/2":true}"#);
+    assert_eq!(serde_json::from_str::<Numeric>(&json).unwrap().0, tree);
+}
+
+#[test]
+fn reports_missing_ancestors() {
+    let err = serde_json::from_value::<Wrapper>(json!({
+        "tree": { "": 0, "/a/b": 2 }
+    }))
+    .unwrap_err();
+    assert!(err.to_string().contains("missing ancestor for path `/a/b`"));
+
+    let err = serde_json::from_value::<Wrapper>(json!({
+        "tree": { "/a": 1 }
+    }))
+    .unwrap_err();
+    assert!(err.to_string().contains("missing the root"));
+}
+
+#[test]
+fn reports_invalid_paths() {
+    let err = serde_json::from_value::<Wrapper>(json!({
+        "tree": { "": 0, "a": 1 }
+    }))
+    .unwrap_err();
+    assert!(err.to_string().contains("doesn't start with `/`"));
+
+    let err = serde_json::from_value::<Wrapper>(json!({
+        "tree": { "": 0, "/a~2": 1 }
+    }))
+    .unwrap_err();
+    assert!(err.to_string().contains("invalid escape sequence"));
+}
+
+#[test]
+fn reports_duplicate_paths() {
+    let err =
+        serde_json::from_str::<Wrapper>(r#"{"tree":{"":0,"/a":1,"/a":2}}"#)
+            .unwrap_err();
+    assert!(err.to_string().contains("duplicate path `/a`"));
+}
@@ -0,0 +1,62 @@
+use tagged_tree::{tree, FlatOptions, Tree};
+
+#[test]
+fn flat_iter_skips_root_by_default() {
+    let tree = tree! { 0; 'a' => 1 { 'b' => 2 }, 'c' => 3 };
+    let flat: Vec<_> = tree.flat_iter().collect();
+    assert_eq!(
+        flat,
+        [(vec![&'a'], &1), (vec![&'a', &'b'], &2), (vec![&'c'], &3)]
+    );
+}
+
+#[test]
+fn flat_iter_can_include_root() {
+    let tree = tree! { 0; 'a' => 1 { 'b' => 2 }, 'c' => 3 };
+    let flat: Vec<_> = tree
+        .flat_iter_with(FlatOptions::new().include_root(true))
+        .map(|(_, value)| *value)
+        .collect();
+    assert_eq!(flat, [0, 1, 2, 3]);
+}
+
+#[test]
+fn flat_iter_can_skip_internal_nodes() {
+    let tree = tree! { 0; 'a' => 1 { 'b' => 2 }, 'c' => 3 };
+    let options = FlatOptions::new()
+        .include_root(true)
+        .include_internal(false);
+    let flat: Vec<_> = tree.flat_iter_with(options).collect();
+    assert_eq!(flat, [(vec![&'a', &'b'], &2), (vec![&'c'], &3)]);
+
+    let single = Tree::<char, i32>::new(7);
+    let flat: Vec<_> = single.flat_iter_with(options).collect();
+    assert_eq!(flat, [(vec![], &7)]);
+}
+
+#[test]
+fn into_flat_matches_flat_iter() {
+    for options in [
+        FlatOptions::new(),
+        FlatOptions::new().include_root(true),
+        FlatOptions::new().include_internal(false),
+    ] {
+        let tree = tree! { 0; 'a' => 1 { 'b' => 2 }, 'c' => 3 };
+        let borrowed: Vec<(Vec<char>, i32)> = tree
+            .flat_iter_with(options)
+            .map(|(path, value)| (path.into_iter().copied().collect(), *value))
+            .collect();
+        let owned: Vec<_> = tree.into_flat_with(options).collect();
+        assert_eq!(borrowed, owned);
+    }
+}
+
+#[test]
+fn into_flat_round_trips_through_collect() {
+    let tree = tree! { 0; 'a' => 1 { 'b' => 2 }, 'c' => 3 };
+    let rebuilt: Tree<char, i32> = tree
+        .clone()
+        .into_flat_with(FlatOptions::new().include_root(true))
+        .collect();
+    assert_eq!(rebuilt, tree);
+}
@@ -0,0 +1,124 @@
+#![cfg(feature = "fs")]
+
+use std::{ffi::OsString, fs};
+use tagged_tree::{fs::DirOptions, glob::GlobSet, Tree};
+
+fn os(key: &str) -> OsString {
+    OsString::from(key)
+}
+
+fn keys(tree: &Tree<OsString, fs::Metadata>) -> Vec<String> {
+    tree.iter_paths()
+        .map(|(path, _)| {
+            let path: Vec<_> =
+                path.iter().map(|k| k.to_string_lossy()).collect();
+            path.join("/")
+        })
+        .collect()
+}
+
+fn make_files() -> Tree<String, Vec<u8>> {
+    let mut files = Tree::new(Vec::new());
+    let src = files.entry("src".to_owned()).or_insert(Vec::new());
+    src.add_child("lib.rs".to_owned(), b"lib".to_vec());
+    src.entry("bin".to_owned())
+        .or_insert(Vec::new())
+        .add_child("main.rs".to_owned(), b"main".to_vec());
+    files.add_child("README.md".to_owned(), b"readme".to_vec());
+    files
+}
+
+#[test]
+fn reads_directories() {
+    let dir = tempfile::tempdir().unwrap();
+    make_files().write_to_dir(dir.path()).unwrap();
+
+    let tree = Tree::from_dir(dir.path(), &DirOptions::new()).unwrap();
+    assert!(tree.value().is_dir());
+    assert_eq!(
+        keys(&tree),
+        [
+            "README.md",
+            "src",
+            "src/bin",
+            "src/bin/main.rs",
+            "src/lib.rs"
+        ]
+    );
+    let main = &tree[&os("src")][&os("bin")][&os("main.rs")];
+    assert!(main.value().is_file());
+    assert_eq!(main.value().len(), 4);
+}
+
+#[test]
+fn max_depth() {
+    let dir = tempfile::tempdir().unwrap();
+    make_files().write_to_dir(dir.path()).unwrap();
+
+    let options = DirOptions::new().max_depth(1);
+    let tree = Tree::from_dir(dir.path(), &options).unwrap();
+    assert_eq!(keys(&tree), ["README.md", "src"]);
+}
+
+#[test]
+fn ignore_patterns() {
+    let dir = tempfile::tempdir().unwrap();
+    make_files().write_to_dir(dir.path()).unwrap();
+
+    let ignore = GlobSet::new(&["*.md", "**/bin"]).unwrap();
+    let options = DirOptions::new().ignore(ignore);
+    let tree = Tree::from_dir(dir.path(), &options).unwrap();
+    assert_eq!(keys(&tree), ["src", "src/lib.rs"]);
+}
+
+#[test]
+fn writes_directories() {
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("out");
+    make_files().write_to_dir(&target).unwrap();
+
+    assert_eq!(fs::read(target.join("src/bin/main.rs")).unwrap(), b"main");
+    assert_eq!(fs::read(target.join("README.md")).unwrap(), b"readme");
+}
+
+#[test]
+fn rejects_invalid_trees() {
+    let dir = tempfile::tempdir().unwrap();
+
+    for key in &["..", "a/b", "", "."] {
+        let mut files = make_files();
+        files.add_child((*key).to_owned(), Vec::new());
+        assert!(files.write_to_dir(dir.path().join("out")).is_err());
+    }
+
+    let mut files = make_files();
+    files
+        .get_child_mut("src")
+        .unwrap()
+        .set_value(b"contents".to_vec());
+    assert!(files.write_to_dir(dir.path().join("out")).is_err());
+    assert!(!dir.path().join("out").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks() {
+    let dir = tempfile::tempdir().unwrap();
+    make_files().write_to_dir(dir.path()).unwrap();
+    std::os::unix::fs::symlink(dir.path().join("src"), dir.path().join("link"))
+        .unwrap();
+    // a link back to an ancestor isn't followed forever
+    std::os::unix::fs::symlink(dir.path(), dir.path().join("src/up")).unwrap();
+
+    let tree = Tree::from_dir(dir.path(), &DirOptions::new()).unwrap();
+    assert!(tree[&os("link")].value().file_type().is_symlink());
+    assert!(tree[&os("link")].is_childless());
+
+    let options = DirOptions::new().follow_symlinks(true);
+    let tree = Tree::from_dir(dir.path(), &options).unwrap();
+    let link = &tree[&os("link")];
+    assert!(link.value().is_dir());
+    assert!(link[&os("bin")].contains_key(&os("main.rs")));
+    assert!(link[&os("up")].value().is_dir());
+    assert!(link[&os("up")].is_childless());
+}
@@ -0,0 +1,47 @@
+#[cfg(feature = "arbitrary")]
+#[test]
+fn reads_trees_from_unstructured_data() {
+    use arbitrary::{Arbitrary, Unstructured};
+    use tagged_tree::Tree;
+
+    let data: Vec<u8> = (0..=255).cycle().take(4096).collect();
+    let mut u = Unstructured::new(&data);
+    let tree = Tree::<u8, u16>::arbitrary(&mut u).unwrap();
+    assert!(tree.iter_paths().all(|(path, _)| path.len() <= 4));
+
+    let tree = Tree::<u8, u16>::arbitrary(&mut Unstructured::new(&[])).unwrap();
+    assert!(tree.is_childless());
+}
+
+#[cfg(feature = "quickcheck")]
+mod quickcheck {
+    use quickcheck::{Arbitrary, Gen, QuickCheck};
+    use tagged_tree::Tree;
+
+    fn sorted<'a, I: Iterator<Item = (&'a u8, &'a u8)>>(
+        nodes: I,
+    ) -> Vec<(u8, u8)> {
+        let mut nodes: Vec<_> = nodes.map(|(k, v)| (*k, *v)).collect();
+        nodes.sort_unstable();
+        nodes
+    }
+
+    #[test]
+    fn traversals_visit_the_same_nodes() {
+        fn property(tree: Tree<u8, u8>) -> bool {
+            sorted(tree.iter_depth_first()) == sorted(tree.iter_breadth_first())
+        }
+        QuickCheck::new().quickcheck(property as fn(Tree<u8, u8>) -> bool);
+    }
+
+    #[test]
+    fn shrinks_by_removing_one_subtree() {
+        let tree = Tree::<u8, u8>::arbitrary(&mut Gen::new(10));
+        let count = tree.iter_paths().count();
+        let shrunk: Vec<_> = tree.shrink().collect();
+        assert_eq!(shrunk.len(), count);
+        for smaller in shrunk {
+            assert!(smaller.iter_paths().count() < count);
+        }
+    }
+}
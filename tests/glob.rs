@@ -0,0 +1,180 @@
+use tagged_tree::{
+    glob::{GlobSet, Pattern},
+    tree, Tree,
+};
+
+type TestSubject = Tree<String, usize>;
+
+fn glob(tree: &TestSubject, pattern: &str) -> Vec<usize> {
+    tree.glob(pattern)
+        .expect("valid pattern")
+        .map(|(_, node)| *node.value())
+        .collect()
+}
+
+#[test]
+fn literal_path() {
+    let tree = tree! { 0;
+        "src".to_owned() => 1 {
+            "lib.rs".to_owned() => 2,
+            "main.rs".to_owned() => 3,
+            "glob".to_owned() => 4 { "mod.rs".to_owned() => 5 },
+        },
+        "tests".to_owned() => 6 {
+            "basic.rs".to_owned() => 7,
+            "glob.txt".to_owned() => 8,
+        },
+        "README.md".to_owned() => 9,
+    };
+    assert_eq!(glob(&tree, "src/glob/mod.rs"), vec![5]);
+    assert!(glob(&tree, "src/missing").is_empty());
+}
+
+#[test]
+fn single_level_wildcards() {
+    let tree = tree! { 0;
+        "src".to_owned() => 1 {
+            "lib.rs".to_owned() => 2,
+            "main.rs".to_owned() => 3,
+            "glob".to_owned() => 4 { "mod.rs".to_owned() => 5 },
+        },
+        "tests".to_owned() => 6 {
+            "basic.rs".to_owned() => 7,
+            "glob.txt".to_owned() => 8,
+        },
+        "README.md".to_owned() => 9,
+    };
+    assert_eq!(glob(&tree, "src/*.rs"), vec![2, 3]);
+    assert_eq!(glob(&tree, "*/????.rs"), vec![3]);
+    assert_eq!(glob(&tree, "*"), vec![9, 1, 6]);
+}
+
+#[test]
+fn recursive_wildcard() {
+    let tree = tree! { 0;
+        "src".to_owned() => 1 {
+            "lib.rs".to_owned() => 2,
+            "main.rs".to_owned() => 3,
+            "glob".to_owned() => 4 { "mod.rs".to_owned() => 5 },
+        },
+        "tests".to_owned() => 6 {
+            "basic.rs".to_owned() => 7,
+            "glob.txt".to_owned() => 8,
+        },
+        "README.md".to_owned() => 9,
+    };
+    assert_eq!(glob(&tree, "**/*.rs"), vec![5, 2, 3, 7]);
+    assert_eq!(glob(&tree, "src/**"), vec![4, 5, 2, 3]);
+    assert_eq!(glob(&tree, "**/glob*"), vec![4, 8]);
+}
+
+#[test]
+fn character_classes() {
+    let tree = tree! { 0;
+        "src".to_owned() => 1 {
+            "lib.rs".to_owned() => 2,
+            "main.rs".to_owned() => 3,
+            "glob".to_owned() => 4 { "mod.rs".to_owned() => 5 },
+        },
+        "tests".to_owned() => 6 {
+            "basic.rs".to_owned() => 7,
+            "glob.txt".to_owned() => 8,
+        },
+        "README.md".to_owned() => 9,
+    };
+    assert_eq!(glob(&tree, "src/[lm]*"), vec![2, 3]);
+    assert_eq!(glob(&tree, "src/[!l]*.rs"), vec![3]);
+    assert_eq!(glob(&tree, "[A-Z]*"), vec![9]);
+}
+
+#[test]
+fn brace_alternation() {
+    let tree = tree! { 0;
+        "src".to_owned() => 1 {
+            "lib.rs".to_owned() => 2,
+            "main.rs".to_owned() => 3,
+            "glob".to_owned() => 4 { "mod.rs".to_owned() => 5 },
+        },
+        "tests".to_owned() => 6 {
+            "basic.rs".to_owned() => 7,
+            "glob.txt".to_owned() => 8,
+        },
+        "README.md".to_owned() => 9,
+    };
+    assert_eq!(glob(&tree, "{src,tests}/{lib,basic}.rs"), vec![2, 7]);
+    assert_eq!(glob(&tree, "src/{glob/mod,main}.rs"), vec![5, 3]);
+}
+
+#[test]
+fn matches_are_reported_with_their_path() {
+    let tree = tree! { 0;
+        "src".to_owned() => 1 {
+            "lib.rs".to_owned() => 2,
+            "main.rs".to_owned() => 3,
+            "glob".to_owned() => 4 { "mod.rs".to_owned() => 5 },
+        },
+        "tests".to_owned() => 6 {
+            "basic.rs".to_owned() => 7,
+            "glob.txt".to_owned() => 8,
+        },
+        "README.md".to_owned() => 9,
+    };
+    let paths: Vec<Vec<&String>> = tree
+        .glob("src/glob/*")
+        .unwrap()
+        .map(|(path, _)| path)
+        .collect();
+    assert_eq!(paths, vec![vec!["src", "glob", "mod.rs"]]);
+}
+
+#[test]
+fn glob_set_matches_in_one_pass() {
+    let tree = tree! { 0;
+        "src".to_owned() => 1 {
+            "lib.rs".to_owned() => 2,
+            "main.rs".to_owned() => 3,
+            "glob".to_owned() => 4 { "mod.rs".to_owned() => 5 },
+        },
+        "tests".to_owned() => 6 {
+            "basic.rs".to_owned() => 7,
+            "glob.txt".to_owned() => 8,
+        },
+        "README.md".to_owned() => 9,
+    };
+    let set = GlobSet::new(&["**/*.rs", "src/*", "README.md"]).unwrap();
+
+    let matches: Vec<(usize, Vec<usize>)> = tree
+        .glob_set(&set)
+        .map(|(_, node, patterns)| (*node.value(), patterns))
+        .collect();
+
+    assert_eq!(
+        matches,
+        vec![
+            (9, vec![2]),
+            (4, vec![1]),
+            (5, vec![0]),
+            (2, vec![0, 1]),
+            (3, vec![0, 1]),
+            (7, vec![0]),
+        ]
+    );
+}
+
+#[test]
+fn pattern_matches_paths() {
+    let pattern: Pattern = "a/**/c".parse().unwrap();
+    assert!(pattern.matches(&["a", "c"]));
+    assert!(pattern.matches(&["a", "b", "b", "c"]));
+    assert!(!pattern.matches(&["a", "b"]));
+    assert!(!pattern.matches(&["b", "c"]));
+}
+
+#[test]
+fn invalid_patterns_are_rejected() {
+    assert!(Pattern::new("a/{b,c").is_err());
+    assert!(Pattern::new("a/[bc").is_err());
+    assert!(Pattern::new("a//b").is_err());
+    assert!(Pattern::new("a/b\\").is_err());
+    assert!(Pattern::new("[z-a]").is_err());
+}
@@ -0,0 +1,190 @@
+#![cfg(feature = "std")]
+
+use std::{fs::OpenOptions, io::Write};
+use tagged_tree::{kv::KvStore, tree, Tree};
+
+type TestSubject = KvStore<String, u32>;
+
+fn key(key: &str) -> String {
+    key.to_owned()
+}
+
+#[test]
+fn empty_store() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = TestSubject::open(dir.path().join("tree.log")).unwrap();
+    assert!(store.is_empty());
+    assert_eq!(store.load().unwrap(), None);
+}
+
+#[test]
+fn saves_and_loads() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tree.log");
+    let tree = tree! { 0;
+        key("a") => 1 { key("b") => 2, key("c") => 3 },
+        key("d") => 4,
+    };
+
+    let mut store = TestSubject::open(&path).unwrap();
+    assert_eq!(store.save_changes(&tree).unwrap(), 5);
+    assert_eq!(store.len(), 5);
+    assert_eq!(store.load().unwrap(), Some(tree.clone()));
+
+    let store = TestSubject::open(&path).unwrap();
+    assert_eq!(store.load().unwrap(), Some(tree.clone()));
+    assert_eq!(
+        store.load_subtree(&[key("a")]).unwrap().as_ref(),
+        tree.get_child(&key("a"))
+    );
+    assert_eq!(store.load_subtree(&[key("x")]).unwrap(), None);
+}
+
+#[test]
+fn only_writes_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tree.log");
+    let mut tree = tree! { 0;
+        key("a") => 1 { key("b") => 2, key("c") => 3 },
+        key("d") => 4,
+    };
+
+    let mut store = TestSubject::open(&path).unwrap();
+    store.save_changes(&tree).unwrap();
+    assert_eq!(store.save_changes(&tree).unwrap(), 0);
+    assert_eq!(store.wasted_bytes(), 0);
+
+    tree.get_child_mut(&key("d")).unwrap().set_value(40);
+    tree.get_child_mut(&key("a"))
+        .unwrap()
+        .add_child(key("e"), 5);
+    let size = std::fs::metadata(&path).unwrap().len();
+    assert_eq!(store.save_changes(&tree).unwrap(), 2);
+    assert!(std::fs::metadata(&path).unwrap().len() > size);
+    assert!(store.wasted_bytes() > 0);
+
+    tree.remove(&key("a"));
+    assert_eq!(store.save_changes(&tree).unwrap(), 4);
+    assert_eq!(store.len(), 2);
+
+    let reopened = TestSubject::open(&path).unwrap();
+    assert_eq!(reopened.load().unwrap(), Some(tree));
+    assert_eq!(reopened.len(), 2);
+    assert_eq!(reopened.wasted_bytes(), store.wasted_bytes());
+}
+
+#[test]
+fn compact_drops_old_records() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tree.log");
+    let mut tree = tree! { 0;
+        key("a") => 1 { key("b") => 2, key("c") => 3 },
+        key("d") => 4,
+    };
+
+    let mut store = TestSubject::open(&path).unwrap();
+    store.save_changes(&tree).unwrap();
+    let size = std::fs::metadata(&path).unwrap().len();
+    for value in 0..10 {
+        tree.set_value(value + 100);
+        store.save_changes(&tree).unwrap();
+    }
+    tree.remove(&key("d"));
+    store.save_changes(&tree).unwrap();
+
+    store.compact().unwrap();
+    assert_eq!(store.wasted_bytes(), 0);
+    assert!(std::fs::metadata(&path).unwrap().len() < size);
+    assert_eq!(store.load().unwrap(), Some(tree.clone()));
+
+    tree.add_child(key("f"), 6);
+    assert_eq!(store.save_changes(&tree).unwrap(), 1);
+    assert_eq!(
+        TestSubject::open(&path).unwrap().load().unwrap(),
+        Some(tree)
+    );
+}
+
+#[test]
+fn drops_partial_records() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tree.log");
+    let tree = tree! { 0;
+        key("a") => 1 { key("b") => 2, key("c") => 3 },
+        key("d") => 4,
+    };
+    TestSubject::open(&path)
+        .unwrap()
+        .save_changes(&tree)
+        .unwrap();
+    let size = std::fs::metadata(&path).unwrap().len();
+
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(&[4, 0, 0, 0, 4, 0, 0]).unwrap();
+    drop(file);
+
+    let store = TestSubject::open(&path).unwrap();
+    assert_eq!(store.load().unwrap(), Some(tree.clone()));
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), size);
+    drop(store);
+
+    // a header claiming more bytes than there are isn't trusted
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(&[4, 0, 0, 0, 0xfe, 0xff, 0xff, 0xff])
+        .unwrap();
+    file.write_all(&[0; 16]).unwrap();
+    drop(file);
+
+    let store = TestSubject::open(&path).unwrap();
+    assert_eq!(store.load().unwrap(), Some(tree));
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), size);
+}
+
+#[test]
+fn compares_values_byte_for_byte() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tree.log");
+    let mut tree = Tree::new(key("abc"));
+    let mut store = KvStore::<String, String>::open(&path).unwrap();
+    store.save_changes(&tree).unwrap();
+
+    tree.set_value(key("abd"));
+    assert_eq!(store.save_changes(&tree).unwrap(), 1);
+    assert_eq!(store.save_changes(&tree).unwrap(), 0);
+    assert_eq!(
+        KvStore::<String, String>::open(&path)
+            .unwrap()
+            .load()
+            .unwrap(),
+        Some(tree)
+    );
+}
+
+#[test]
+fn rejects_other_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tree.log");
+    std::fs::write(&path, b"something else").unwrap();
+    assert!(TestSubject::open(&path).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn failed_compactions_leave_no_temp_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tree.log");
+    let tree = tree! { 0;
+        key("a") => 1 { key("b") => 2, key("c") => 3 },
+        key("d") => 4,
+    };
+    let mut store = TestSubject::open(&path).unwrap();
+    store.save_changes(&tree).unwrap();
+
+    // a directory can't be replaced by the compacted log
+    std::fs::remove_file(&path).unwrap();
+    std::fs::create_dir(&path).unwrap();
+    std::fs::write(path.join("other"), b"").unwrap();
+    assert!(store.compact().is_err());
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    assert_eq!(store.load().unwrap(), Some(tree));
+}
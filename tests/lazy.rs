@@ -0,0 +1,111 @@
+use std::cell::RefCell;
+use tagged_tree::{
+    lazy::{LazyTree, Loader},
+    Tree,
+};
+
+/// Serves children out of a tree in memory and records the paths it loads
+struct MemoryLoader {
+    tree: Tree<char, i32>,
+    loaded: RefCell<Vec<Vec<char>>>,
+}
+
+impl Loader<char, i32> for MemoryLoader {
+    fn load_children(&self, path: &[char], _value: &i32) -> Vec<(char, i32)> {
+        self.loaded.borrow_mut().push(path.to_vec());
+        path.iter()
+            .try_fold(&self.tree, |node, key| node.get_child(key))
+            .expect("only existing nodes are loaded")
+            .iter_single()
+            .map(|(key, child)| (*key, *child.value()))
+            .collect()
+    }
+}
+
+fn make_lazy_tree() -> LazyTree<char, i32, MemoryLoader> {
+    let mut tree = Tree::new(0);
+    let a = tree.entry('a').or_insert(1);
+    a.entry('b').or_insert(2).add_child('c', 3);
+    a.add_child('d', 4);
+    tree.add_child('e', 5);
+    LazyTree::new(
+        0,
+        MemoryLoader {
+            tree,
+            loaded: RefCell::new(Vec::new()),
+        },
+    )
+}
+
+fn loaded(tree: &LazyTree<char, i32, MemoryLoader>) -> Vec<Vec<char>> {
+    tree.loader().loaded.borrow().clone()
+}
+
+#[test]
+fn children_are_loaded_once() {
+    let tree = make_lazy_tree();
+    assert!(!tree.is_expanded());
+    assert!(loaded(&tree).is_empty());
+
+    let a = tree.get_child(&'a').expect("a");
+    assert_eq!(*a.value(), 1);
+    assert_eq!(a.path(), ['a']);
+    assert!(tree.get_child(&'e').is_some());
+    assert!(tree.get_child(&'z').is_none());
+
+    assert!(tree.is_expanded());
+    assert!(!a.is_expanded());
+    assert_eq!(loaded(&tree), [vec![]]);
+}
+
+#[test]
+fn iterating_loads_children() {
+    let tree = make_lazy_tree();
+    let a = tree.get_child(&'a').expect("a");
+    let children: Vec<_> = a
+        .iter_single()
+        .map(|(key, child)| (*key, *child.value()))
+        .collect();
+    assert_eq!(children, [('b', 2), ('d', 4)]);
+    assert!(a.is_expanded());
+    assert_eq!(loaded(&tree), [vec![], vec!['a']]);
+}
+
+#[test]
+fn collapsing_drops_children() {
+    let mut tree = make_lazy_tree();
+    *tree.get_child_mut(&'e').expect("e").value_mut() = 50;
+    assert_eq!(*tree.get_child(&'e').expect("e").value(), 50);
+
+    tree.collapse();
+    assert!(!tree.is_expanded());
+    assert_eq!(*tree.get_child(&'e').expect("e").value(), 5);
+    assert_eq!(loaded(&tree), [vec![], vec![]]);
+}
+
+#[test]
+fn expand_all_stops_at_max_depth() {
+    let tree = make_lazy_tree();
+    tree.expand_all(0);
+    assert!(!tree.is_expanded());
+
+    tree.expand_all(2);
+    let a = tree.get_child(&'a').expect("a");
+    assert!(a.is_expanded());
+    assert!(!a.get_child(&'b').expect("b").is_expanded());
+
+    tree.expand_all(10);
+    let mut paths = loaded(&tree);
+    paths.sort();
+    assert_eq!(
+        paths,
+        [
+            vec![],
+            vec!['a'],
+            vec!['a', 'b'],
+            vec!['a', 'b', 'c'],
+            vec!['a', 'd'],
+            vec!['e'],
+        ]
+    );
+}
@@ -0,0 +1,74 @@
+use tagged_tree::{tree, Tree};
+
+#[test]
+fn builds_a_lone_root() {
+    let tree: Tree<&str, i32> = tree! { 5 };
+    assert_eq!(tree, Tree::new(5));
+
+    let tree: Tree<&str, i32> = tree! { 5; };
+    assert_eq!(tree, Tree::new(5));
+}
+
+#[test]
+fn builds_nested_children() {
+    let tree = tree! { 0;
+        "a" => 1 {
+            "b" => 2,
+            "c" => 3 { "e" => 5 },
+        },
+        "d" => 4,
+    };
+
+    let mut expected = Tree::new(0);
+    let (_, a) = expected.add_child("a", 1);
+    a.add_child("b", 2);
+    a.add_child("c", 3).1.add_child("e", 5);
+    expected.add_child("d", 4);
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn accepts_arbitrary_expressions() {
+    let offset = 10;
+    let tree = tree! { offset * 2;
+        1 + 1 => (-offset) { 3 => { offset + 3 } },
+        "four".len() => (vec![4].len() as i32),
+    };
+
+    assert_eq!(*tree.value(), 20);
+    assert_eq!(*tree[&2].value(), -10);
+    assert_eq!(*tree[&2][&3].value(), 13);
+    assert_eq!(*tree[&4].value(), 1);
+}
+
+#[test]
+fn repeated_keys_are_merged() {
+    let tree = tree! { 0;
+        'a' => 1 { 'b' => 2 },
+        'a' => 3 { 'c' => 4 },
+    };
+
+    assert_eq!(*tree[&'a'].value(), 3);
+    assert_eq!(tree[&'a'].children().len(), 2);
+}
+
+#[test]
+fn builds_many_siblings() {
+    let tree = tree! { 0;
+        0 => 0, 1 => 1, 2 => 2, 3 => 3, 4 => 4, 5 => 5, 6 => 6, 7 => 7,
+        8 => 8, 9 => 9, 10 => 10, 11 => 11, 12 => 12, 13 => 13, 14 => 14,
+        15 => 15, 16 => 16, 17 => 17, 18 => 18, 19 => 19, 20 => 20,
+        21 => 21, 22 => 22, 23 => 23, 24 => 24, 25 => 25, 26 => 26,
+        27 => 27, 28 => 28, 29 => 29, 30 => 30, 31 => 31, 32 => 32,
+        33 => 33, 34 => 34, 35 => 35, 36 => 36, 37 => 37, 38 => 38,
+        39 => 39, 40 => 40, 41 => 41, 42 => 42, 43 => 43, 44 => 44,
+        45 => 45, 46 => 46, 47 => 47, 48 => 48, 49 => 49, 50 => 50,
+        51 => 51, 52 => 52, 53 => 53, 54 => 54, 55 => 55, 56 => 56,
+        57 => 57, 58 => 58, 59 => 59, 60 => 60, 61 => 61, 62 => 62,
+        63 => 63, 64 => 64, 65 => 65, 66 => 66, 67 => 67, 68 => 68,
+        69 => 69, 70 => 70, 71 => 71, 72 => 72, 73 => 73, 74 => 74,
+    };
+
+    assert_eq!(tree.children().len(), 75);
+    assert!(tree.iter_single().all(|(key, child)| child.value() == key));
+}
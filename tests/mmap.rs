@@ -0,0 +1,218 @@
+#![cfg(feature = "mmap")]
+
+use std::path::{Path, PathBuf};
+use tagged_tree::{mmap::MappedTree, tree, Tree};
+use tempfile::TempDir;
+
+type TestSubject = MappedTree<String, u32>;
+
+fn key(key: &str) -> String {
+    key.to_owned()
+}
+
+fn path(keys: &[&str]) -> Vec<String> {
+    keys.iter().map(|k| key(k)).collect()
+}
+
+fn write(dir: &TempDir, tree: &Tree<String, u32>) -> PathBuf {
+    let path = dir.path().join("tree.bin");
+    std::fs::write(&path, tree.to_binary().unwrap()).unwrap();
+    path
+}
+
+fn open(path: &Path) -> TestSubject {
+    TestSubject::open(path).unwrap()
+}
+
+#[test]
+fn reads_from_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let tree = tree! { 0;
+        key("a") => 1 { key("b") => 2, key("c") => 3 },
+        key("d") => 4,
+    };
+    let mapped = open(&write(&dir, &tree));
+
+    let root = mapped.root();
+    assert_eq!(*root.value(), 0);
+    assert_eq!(root.children_keys(), path(&["a", "d"]));
+    assert_eq!(root.child_count(), 2);
+    assert_eq!(*mapped.get_path(&path(&["a", "c"])).unwrap().value(), 3);
+    assert!(mapped.get_path(&path(&["a", "d"])).is_none());
+    assert!(!mapped.is_modified());
+    assert_eq!(mapped.to_tree(), tree);
+}
+
+#[test]
+fn rejects_malformed_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tree.bin");
+    std::fs::write(&path, b"not a tree").unwrap();
+    assert!(TestSubject::open(&path).is_err());
+}
+
+#[test]
+fn changes_are_visible_before_saving() {
+    let dir = tempfile::tempdir().unwrap();
+    let tree = tree! { 0;
+        key("a") => 1 { key("b") => 2, key("c") => 3 },
+        key("d") => 4,
+    };
+    let mut mapped = open(&write(&dir, &tree));
+
+    assert_eq!(mapped.set_value(&path(&["a", "b"]), 20), Some(2));
+    assert_eq!(mapped.set_value(&path(&["a", "x"]), 20), None);
+    assert!(mapped.insert(&path(&["a", "e"]), Tree::new(5)));
+    assert!(!mapped.insert(&path(&["x", "y"]), Tree::new(5)));
+    assert!(mapped.remove(&path(&["d"])));
+    assert!(!mapped.remove(&path(&["d"])));
+    assert!(mapped.is_modified());
+
+    let mut expected = tree.clone();
+    expected.remove(&key("d"));
+    let a = expected.get_child_mut(&key("a")).unwrap();
+    a.get_child_mut(&key("b")).unwrap().set_value(20);
+    a.add_child(key("e"), 5);
+    assert_eq!(mapped.to_tree(), expected);
+    assert_eq!(mapped.root().children_keys(), path(&["a"]));
+
+    mapped.discard_changes();
+    assert_eq!(mapped.to_tree(), tree);
+}
+
+#[test]
+fn get_mut_copies_the_subtree() {
+    let dir = tempfile::tempdir().unwrap();
+    let tree = tree! { 0;
+        key("a") => 1 { key("b") => 2, key("c") => 3 },
+        key("d") => 4,
+    };
+    let mut mapped = open(&write(&dir, &tree));
+
+    mapped.set_value(&path(&["a", "b"]), 20);
+    let a = mapped.get_mut(&path(&["a"])).unwrap();
+    assert_eq!(*a.get_child(&key("b")).unwrap().value(), 20);
+    a.add_child(key("e"), 5);
+    *mapped.get_mut(&path(&["a", "e"])).unwrap().value_mut() = 50;
+
+    let a = mapped.get_path(&path(&["a"])).unwrap();
+    assert_eq!(a.children_keys(), path(&["b", "c", "e"]));
+    assert_eq!(*a.get_child(&key("e")).unwrap().value(), 50);
+    assert!(mapped.get_mut(&path(&["x"])).is_none());
+}
+
+#[test]
+fn get_mut_returns_replaced_children() {
+    let dir = tempfile::tempdir().unwrap();
+    let tree = tree! { 0;
+        key("a") => 1 { key("b") => 2, key("c") => 3 },
+        key("d") => 4,
+    };
+    let mut mapped = open(&write(&dir, &tree));
+
+    mapped.get_mut(&path(&["a"])).unwrap().set_value(10);
+    mapped
+        .get_mut(&path(&["a"]))
+        .unwrap()
+        .add_child(key("e"), 5);
+    let a = mapped.get_path(&path(&["a"])).unwrap();
+    assert_eq!(*a.value(), 10);
+    assert_eq!(a.children_keys(), path(&["b", "c", "e"]));
+
+    assert!(mapped.insert(&path(&["x"]), Tree::new(6)));
+    mapped.get_mut(&path(&["x"])).unwrap().set_value(60);
+    assert_eq!(*mapped.get_path(&path(&["x"])).unwrap().value(), 60);
+}
+
+#[test]
+fn missing_paths_leave_no_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let tree = tree! { 0;
+        key("a") => 1 { key("b") => 2, key("c") => 3 },
+        key("d") => 4,
+    };
+    let mut mapped = open(&write(&dir, &tree));
+
+    assert_eq!(mapped.set_value(&path(&["a", "b", "x", "y"]), 20), None);
+    assert!(!mapped.remove(&path(&["a", "b", "x"])));
+    assert!(!mapped.insert(&path(&["a", "x", "y"]), Tree::new(5)));
+    assert!(mapped.get_mut(&path(&["a", "c", "x"])).is_none());
+    assert!(!mapped.is_modified());
+}
+
+#[test]
+fn save_writes_the_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let tree = tree! { 0;
+        key("a") => 1 { key("b") => 2, key("c") => 3 },
+        key("d") => 4,
+    };
+    let file = write(&dir, &tree);
+    let mut mapped = open(&file);
+
+    mapped.set_value(&path(&["a"]), 10);
+    mapped.insert(&path(&["a", "b", "x"]), Tree::new(6));
+    mapped.remove(&path(&["a", "c"]));
+    let expected = mapped.to_tree();
+    mapped.save().unwrap();
+
+    assert!(!mapped.is_modified());
+    assert_eq!(mapped.to_tree(), expected);
+    assert_eq!(open(&file).to_tree(), expected);
+    assert_eq!(std::fs::read(&file).unwrap(), expected.to_binary().unwrap());
+
+    // nothing is left next to the file
+    let files = std::fs::read_dir(dir.path()).unwrap().count();
+    assert_eq!(files, 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn failed_saves_leave_the_tree_usable() {
+    let dir = tempfile::tempdir().unwrap();
+    let tree = tree! { 0;
+        key("a") => 1 { key("b") => 2, key("c") => 3 },
+        key("d") => 4,
+    };
+    let file = write(&dir, &tree);
+    let mut mapped = open(&file);
+    mapped.set_value(&path(&["a"]), 10);
+    let expected = mapped.to_tree();
+
+    // a directory can't be replaced by the new file, nor mapped
+    std::fs::remove_file(&file).unwrap();
+    std::fs::create_dir(&file).unwrap();
+    std::fs::write(file.join("other"), b"").unwrap();
+    assert!(mapped.save().is_err());
+    assert_eq!(mapped.to_tree(), expected);
+    assert_eq!(mapped.get_mut(&path(&["a"])).unwrap().value(), &10);
+
+    std::fs::remove_dir_all(&file).unwrap();
+    mapped.save().unwrap();
+    assert_eq!(open(&file).to_tree(), expected);
+}
+
+#[test]
+fn saves_large_trees() {
+    let mut tree = Tree::new(0u32);
+    for i in 0..2000u32 {
+        let child = tree.entry(i).or_insert(i);
+        for j in 0..10 {
+            child.add_child(j, j);
+        }
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("tree.bin");
+    std::fs::write(&file, tree.to_binary().unwrap()).unwrap();
+
+    let mut mapped = MappedTree::<u32, u32>::open(&file).unwrap();
+    assert_eq!(mapped.set_value(&[1500, 5], 100), Some(5));
+    mapped.save().unwrap();
+
+    tree.get_child_mut(&1500)
+        .unwrap()
+        .get_child_mut(&5)
+        .unwrap()
+        .set_value(100);
+    assert_eq!(std::fs::read(&file).unwrap(), tree.to_binary().unwrap());
+}
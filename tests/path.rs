@@ -0,0 +1,88 @@
+use tagged_tree::{
+    path::{common_prefix, is_ancestor, lca, relative_path},
+    tree, Tree,
+};
+
+#[test]
+fn common_prefix_stops_at_first_difference() {
+    assert_eq!(common_prefix(&[1, 2, 3], &[1, 2, 4, 5]), [1, 2]);
+    assert_eq!(common_prefix(&[1, 2], &[1, 2, 3]), [1, 2]);
+    assert_eq!(common_prefix(&[1], &[2]), [0; 0]);
+    assert_eq!(common_prefix::<i32>(&[], &[1]), [0; 0]);
+}
+
+#[test]
+fn lca_of_many_paths() {
+    let paths = [vec![1, 2, 3], vec![1, 2, 4], vec![1, 2]];
+    assert_eq!(lca(&paths), Some(&[1, 2][..]));
+    assert_eq!(lca([&[1, 2, 3][..], &[1, 5]]), Some(&[1][..]));
+    assert_eq!(lca([&[1, 2][..]]), Some(&[1, 2][..]));
+    assert_eq!(lca::<i32, Vec<i32>, _>(&[]), None);
+}
+
+#[test]
+fn nodes_are_not_their_own_ancestors() {
+    assert!(is_ancestor(&[], &[1]));
+    assert!(is_ancestor(&[1], &[1, 2, 3]));
+    assert!(!is_ancestor(&[1, 2], &[1, 2]));
+    assert!(!is_ancestor(&[1, 2], &[1]));
+    assert!(!is_ancestor(&[1, 3], &[1, 2, 3]));
+}
+
+#[test]
+fn relative_path_goes_up_then_down() {
+    let relative = relative_path(&[1, 2, 3], &[1, 4]);
+    assert_eq!(relative.up(), 2);
+    assert_eq!(relative.down(), [4]);
+    assert_eq!(relative.len(), 3);
+
+    let relative = relative_path(&[1], &[1, 2, 3]);
+    assert_eq!(relative.up(), 0);
+    assert_eq!(relative.down(), [2, 3]);
+
+    let relative = relative_path(&[1, 2], &[1, 2]);
+    assert!(relative.is_empty());
+}
+
+#[test]
+fn subtree_at_follows_keys() {
+    let mut tree = tree! { 0;
+        "a" => 1 { "b" => 2 { "c" => 3 }, "d" => 4 },
+        "e" => 5,
+    };
+    assert_eq!(*tree.subtree_at(["a", "b", "c"]).unwrap().value(), 3);
+    assert_eq!(*tree.subtree_at(&["a", "d"]).unwrap().value(), 4);
+    assert_eq!(*tree.subtree_at(Vec::<&str>::new()).unwrap().value(), 0);
+    assert!(tree.subtree_at(["a", "e"]).is_none());
+
+    tree.subtree_at_mut(["e"]).unwrap().set_value(50);
+    assert_eq!(tree["e"].value(), &50);
+}
+
+#[test]
+fn subtree_at_borrows_keys() {
+    let mut tree = Tree::new(0);
+    tree.entry("a".to_owned())
+        .or_insert(1)
+        .add_child("b".to_owned(), 2);
+
+    assert_eq!(tree.value_at(["a", "b"]), Some(&2));
+    assert_eq!(tree.value_at(&["a".to_owned()]), Some(&1));
+    assert!(tree.contains_path(vec!["a"]));
+    assert!(!tree.contains_path(["b"]));
+
+    *tree.value_at_mut(["a", "b"]).unwrap() = 20;
+    assert_eq!(*tree.subtree_at(["a"]).unwrap()["b"].value(), 20);
+}
+
+#[test]
+fn lca_node_of_two_paths() {
+    let tree = tree! { 0;
+        "a" => 1 { "b" => 2 { "c" => 3 }, "d" => 4 },
+        "e" => 5,
+    };
+    let c = ["a", "b", "c"];
+    let d = ["a", "d"];
+    let ancestor = tree.subtree_at(common_prefix(&c, &d)).unwrap();
+    assert_eq!(*ancestor.value(), 1);
+}
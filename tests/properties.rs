@@ -0,0 +1,69 @@
+#![cfg(feature = "proptest")]
+
+use proptest::{
+    prelude::*,
+    strategy::ValueTree,
+    test_runner::{Config, TestRunner},
+};
+use tagged_tree::{strategy::trees, Tree};
+
+fn sorted<'a, I>(nodes: I) -> Vec<(u8, i32)>
+where
+    I: Iterator<Item = (&'a u8, &'a i32)>,
+{
+    let mut nodes: Vec<_> = nodes.map(|(k, v)| (*k, *v)).collect();
+    nodes.sort_unstable();
+    nodes
+}
+
+fn depth(tree: &Tree<u8, i32>) -> usize {
+    tree.iter_paths()
+        .map(|(path, _)| path.len())
+        .max()
+        .unwrap_or(0)
+}
+
+proptest! {
+    #[test]
+    fn traversals_visit_the_same_nodes(tree in trees(any::<u8>(), any::<i32>(), 4, 5)) {
+        prop_assert_eq!(
+            sorted(tree.iter_depth_first()),
+            sorted(tree.iter_breadth_first())
+        );
+        prop_assert_eq!(tree.iter_depth_first().count(), tree.iter_paths().count());
+    }
+
+    #[test]
+    fn respects_the_bounds(tree in trees(any::<u8>(), any::<i32>(), 3, 2)) {
+        prop_assert!(depth(&tree) <= 3);
+        for (_, node) in tree.iter_paths() {
+            prop_assert!(node.children().len() <= 2);
+        }
+        prop_assert!(tree.children().len() <= 2);
+    }
+}
+
+#[test]
+fn shrinks_by_removing_subtrees() {
+    let mut runner = TestRunner::new(Config::default());
+    let strategy = trees(any::<u8>(), Just(0), 3, 4);
+
+    // find a tree deep enough to fail the property
+    let mut value = loop {
+        let value = strategy.new_tree(&mut runner).unwrap();
+        if depth(&value.current()) == 3 {
+            break value;
+        }
+    };
+
+    // keep simplifying while the property still fails
+    while value.simplify() {
+        if depth(&value.current()) < 3 && !value.complicate() {
+            break;
+        }
+    }
+
+    let minimal = value.current();
+    assert_eq!(depth(&minimal), 3);
+    assert_eq!(minimal.iter_paths().count(), 3);
+}
@@ -0,0 +1,191 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+use tagged_tree::{tree, RadixTree, Tree};
+
+type TestSubject = RadixTree<char, usize>;
+
+fn hash(tree: &TestSubject) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    tree.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn converts_from_and_into_tree() {
+    let tree = tree! { 0;
+        'a' => 1 {
+            'b' => 2 { 'c' => 3 { 'd' => 4 }, 'e' => 5 { 'f' => 6 } },
+        },
+    };
+    let radix = TestSubject::from(tree.clone());
+
+    assert_eq!(radix.edge_count(), 3);
+    assert_eq!(Tree::from(radix), tree);
+}
+
+#[test]
+fn converts_deep_trees() {
+    // every node on the spine has a leaf next to it, so no chain is merged
+    let mut tree = Tree::new(0);
+    let mut node = &mut tree;
+    for depth in 1..100_000 {
+        node.add_child('a', depth);
+        node = node.entry('b').or_insert(depth);
+    }
+
+    let radix = TestSubject::from(tree.clone());
+    assert_eq!(radix.edge_count(), 2 * 99_999);
+
+    let clone = radix.clone();
+    assert_eq!(clone, radix);
+    assert_eq!(hash(&clone), hash(&radix));
+    assert_eq!(Tree::from(radix), tree);
+}
+
+#[test]
+fn clone_eq_and_hash() {
+    let tree = tree! { 0;
+        'a' => 1 {
+            'b' => 2 { 'c' => 3 { 'd' => 4 }, 'e' => 5 { 'f' => 6 } },
+        },
+    };
+    let radix = TestSubject::from(tree);
+    let mut other = radix.clone();
+    assert_eq!(other, radix);
+    assert_eq!(hash(&other), hash(&radix));
+
+    *other.value_at_mut("abc".chars()).unwrap() = 30;
+    assert_ne!(other, radix);
+    assert_ne!(hash(&other), hash(&radix));
+}
+
+#[test]
+fn debug_lists_every_node() {
+    let mut radix = TestSubject::new(0);
+    radix.insert("ab".chars(), 2);
+    radix.insert("c".chars(), 3);
+    assert_eq!(
+        format!("{:?}", radix),
+        "{[]: 0, ['a']: 0, ['a', 'b']: 2, ['c']: 3}"
+    );
+}
+
+#[test]
+fn path_lookup_matches_tree() {
+    let tree = tree! { 0;
+        'a' => 1 {
+            'b' => 2 { 'c' => 3 { 'd' => 4 }, 'e' => 5 { 'f' => 6 } },
+        },
+    };
+    let radix = TestSubject::from(tree.clone());
+    for path in ["", "a", "abc", "abef", "ac", "abcde"] {
+        let keys: Vec<char> = path.chars().collect();
+        assert_eq!(radix.value_at(path.chars()), tree.value_at(&keys));
+        assert_eq!(
+            radix.contains_path(path.chars()),
+            tree.contains_path(&keys)
+        );
+    }
+}
+
+#[test]
+fn path_lookup() {
+    let tree = tree! { 0;
+        'a' => 1 {
+            'b' => 2 { 'c' => 3 { 'd' => 4 }, 'e' => 5 { 'f' => 6 } },
+        },
+    };
+    let radix = TestSubject::from(tree);
+
+    assert_eq!(radix.value_at("".chars()), Some(&0));
+    assert_eq!(radix.value_at("a".chars()), Some(&1));
+    assert_eq!(radix.value_at("ab".chars()), Some(&2));
+    assert_eq!(radix.value_at("abc".chars()), Some(&3));
+    assert_eq!(radix.value_at("abcd".chars()), Some(&4));
+    assert_eq!(radix.value_at("abef".chars()), Some(&6));
+    assert_eq!(radix.value_at("ac".chars()), None);
+    assert_eq!(radix.value_at("abcde".chars()), None);
+    assert!(radix.contains_path(['a', 'b', 'e'].iter()));
+}
+
+#[test]
+fn value_at_mut_in_the_middle_of_an_edge() {
+    let tree = tree! { 0;
+        'a' => 1 {
+            'b' => 2 { 'c' => 3 { 'd' => 4 }, 'e' => 5 { 'f' => 6 } },
+        },
+    };
+    let mut radix = TestSubject::from(tree);
+    *radix.value_at_mut("abc".chars()).unwrap() = 30;
+    assert_eq!(radix.value_at("abc".chars()), Some(&30));
+    assert_eq!(radix.edge_count(), 3);
+}
+
+#[test]
+fn insert_splits_edges() {
+    let mut radix = TestSubject::new(0);
+    assert_eq!(radix.insert("abcd".chars(), 4), None);
+    assert_eq!(radix.edge_count(), 1);
+    assert_eq!(radix.value_at("ab".chars()), Some(&0));
+
+    assert_eq!(radix.insert("abx".chars(), 7), None);
+    assert_eq!(radix.edge_count(), 3);
+    assert_eq!(radix.value_at("abx".chars()), Some(&7));
+    assert_eq!(radix.value_at("abcd".chars()), Some(&4));
+
+    assert_eq!(radix.insert("ab".chars(), 2), Some(0));
+    assert_eq!(radix.value_at("ab".chars()), Some(&2));
+}
+
+#[test]
+fn insert_extends_leaf_edges() {
+    let mut radix = TestSubject::new(0);
+    radix.insert("ab".chars(), 2);
+    radix.insert("abcd".chars(), 4);
+    assert_eq!(radix.edge_count(), 1);
+    assert_eq!(radix.value_at("abc".chars()), Some(&0));
+    assert_eq!(radix.value_at("abcd".chars()), Some(&4));
+}
+
+#[test]
+fn remove_merges_edges() {
+    let tree = tree! { 0;
+        'a' => 1 {
+            'b' => 2 { 'c' => 3 { 'd' => 4 }, 'e' => 5 { 'f' => 6 } },
+        },
+    };
+    let mut radix = TestSubject::from(tree);
+
+    let removed = radix.remove("abef".chars()).unwrap();
+    assert_eq!(*removed.value(), 6);
+    assert_eq!(radix.edge_count(), 3);
+
+    let removed = radix.remove("abe".chars()).unwrap();
+    assert_eq!(*removed.value(), 5);
+    assert_eq!(radix.edge_count(), 1);
+    assert_eq!(radix.value_at("abcd".chars()), Some(&4));
+
+    assert!(radix.remove("abe".chars()).is_none());
+    assert!(radix.remove("".chars()).is_none());
+}
+
+#[test]
+fn remove_returns_the_subtree() {
+    let tree = tree! { 0;
+        'a' => 1 {
+            'b' => 2 { 'c' => 3 { 'd' => 4 }, 'e' => 5 { 'f' => 6 } },
+        },
+    };
+    let mut radix = TestSubject::from(tree);
+
+    let removed = radix.remove("ab".chars()).unwrap();
+    assert_eq!(radix.value_at("a".chars()), Some(&1));
+    assert!(!radix.contains_path("ab".chars()));
+
+    let removed = Tree::from(removed);
+    assert_eq!(*removed.value(), 2);
+    assert_eq!(*removed[&'c'][&'d'].value(), 4);
+    assert_eq!(*removed[&'e'][&'f'].value(), 6);
+}
@@ -0,0 +1,74 @@
+use tagged_tree::{tree, Tree};
+
+#[test]
+fn range_children() {
+    let tree = tree! { 0;
+        1 => 10, 2 => 20, 3 => 30,
+        4 => 40, 5 => 50, 6 => 60,
+        7 => 70, 8 => 80, 9 => 90,
+    };
+    let keys: Vec<usize> =
+        tree.range_children(3..6).map(|(key, _)| *key).collect();
+    assert_eq!(keys, vec![3, 4, 5]);
+
+    let keys: Vec<usize> =
+        tree.range_children(8..).map(|(key, _)| *key).collect();
+    assert_eq!(keys, vec![8, 9]);
+}
+
+#[test]
+fn range_children_mut() {
+    let mut tree = tree! { 0;
+        1 => 10, 2 => 20, 3 => 30,
+        4 => 40, 5 => 50, 6 => 60,
+        7 => 70, 8 => 80, 9 => 90,
+    };
+    for (_, child) in tree.range_children_mut(..=2) {
+        *child.value_mut() = 0;
+    }
+    let values: Vec<usize> =
+        tree.children().map(|child| *child.value()).collect();
+    assert_eq!(values, vec![0, 0, 30, 40, 50, 60, 70, 80, 90]);
+}
+
+#[test]
+fn children_with_prefix() {
+    let mut tree: Tree<String, usize> = Tree::new(0);
+    for (i, key) in ["app", "apple", "application", "apt", "b", "ap"]
+        .iter()
+        .enumerate()
+    {
+        tree.add_child(key.to_string(), i);
+    }
+    let keys: Vec<&str> = tree
+        .children_with_prefix("app")
+        .map(|(key, _)| key.as_str())
+        .collect();
+    assert_eq!(keys, vec!["app", "apple", "application"]);
+
+    assert_eq!(tree.children_with_prefix("ap").count(), 5);
+    assert_eq!(tree.children_with_prefix("").count(), 6);
+    assert_eq!(tree.children_with_prefix("c").count(), 0);
+}
+
+#[test]
+fn first_and_last_child() {
+    let mut tree = tree! { 0;
+        1 => 10, 2 => 20, 3 => 30,
+        4 => 40, 5 => 50, 6 => 60,
+        7 => 70, 8 => 80, 9 => 90,
+    };
+    assert_eq!(tree.first_child().map(|(key, _)| *key), Some(1));
+    assert_eq!(tree.last_child().map(|(key, _)| *key), Some(9));
+
+    let (key, child) = tree.pop_first_child().unwrap();
+    assert_eq!((key, *child.value()), (1, 10));
+    let (key, child) = tree.pop_last_child().unwrap();
+    assert_eq!((key, *child.value()), (9, 90));
+    assert_eq!(tree.first_child().map(|(key, _)| *key), Some(2));
+    assert_eq!(tree.last_child().map(|(key, _)| *key), Some(8));
+
+    let mut empty = Tree::<usize, usize>::new(0);
+    assert!(empty.first_child().is_none());
+    assert!(empty.pop_last_child().is_none());
+}
@@ -0,0 +1,240 @@
+#![cfg(feature = "stream")]
+
+use futures_executor::block_on;
+use futures_util::StreamExt;
+use std::{
+    cell::Cell,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+use tagged_tree::{lazy::LazyTree, stream::ChildLoader, tree, Tree};
+
+/// Serves children out of a tree in memory, each load taking one extra poll
+struct MemoryLoader {
+    tree: Tree<char, i32>,
+    in_flight: Rc<Cell<usize>>,
+    max_in_flight: Rc<Cell<usize>>,
+    loads: Cell<usize>,
+}
+
+impl MemoryLoader {
+    fn new(tree: Tree<char, i32>) -> Self {
+        MemoryLoader {
+            tree,
+            in_flight: Rc::new(Cell::new(0)),
+            max_in_flight: Rc::new(Cell::new(0)),
+            loads: Cell::new(0),
+        }
+    }
+}
+
+type Lazy = LazyTree<char, i32, MemoryLoader>;
+
+fn node(node: &Lazy) -> (Vec<char>, i32) {
+    (node.path().to_vec(), *node.value())
+}
+
+struct Load {
+    children: Option<Result<Vec<(char, i32)>, String>>,
+    polled: bool,
+    in_flight: Rc<Cell<usize>>,
+}
+
+impl Future for Load {
+    type Output = Result<Vec<(char, i32)>, String>;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        if !self.polled {
+            self.polled = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        self.in_flight.set(self.in_flight.get() - 1);
+        Poll::Ready(self.children.take().expect("polled after completion"))
+    }
+}
+
+impl ChildLoader<char, i32> for MemoryLoader {
+    type Error = String;
+    type Future = Load;
+
+    fn load_children(&self, path: &[char], value: &i32) -> Self::Future {
+        self.loads.set(self.loads.get() + 1);
+        let in_flight = self.in_flight.get() + 1;
+        self.in_flight.set(in_flight);
+        self.max_in_flight
+            .set(self.max_in_flight.get().max(in_flight));
+
+        let children = match path
+            .iter()
+            .try_fold(&self.tree, |node, key| node.get_child(key))
+        {
+            Some(node) if node.value() != value => {
+                Err(format!("wrong value for {:?}", path))
+            }
+            Some(_) if *value < 0 => Err(format!("can't load {:?}", path)),
+            Some(node) => Ok(node
+                .iter_single()
+                .map(|(key, child)| (*key, *child.value()))
+                .collect()),
+            None => Err(format!("no node at {:?}", path)),
+        };
+        Load {
+            children: Some(children),
+            polled: false,
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+#[test]
+fn depth_first_stream_matches_tree() {
+    let tree = tree! { 0;
+        'a' => 1 { 'b' => 2 { 'c' => 3 }, 'd' => 4 },
+        'e' => 5 { 'f' => 6, 'g' => 7 },
+        'h' => 8,
+    };
+    let expected: Vec<_> = tree
+        .iter_paths()
+        .map(|(path, node)| {
+            (path.into_iter().copied().collect::<Vec<_>>(), *node.value())
+        })
+        .collect();
+
+    for concurrency in 1..5 {
+        let lazy = LazyTree::new(0, MemoryLoader::new(tree.clone()));
+        let nodes: Vec<_> = block_on(
+            lazy.stream_depth_first(concurrency)
+                .map(|loaded| node(loaded.unwrap()))
+                .collect(),
+        );
+        assert_eq!(nodes, expected);
+    }
+}
+
+#[test]
+fn breadth_first_stream_matches_tree() {
+    let tree = tree! { 0;
+        'a' => 1 { 'b' => 2 { 'c' => 3 }, 'd' => 4 },
+        'e' => 5 { 'f' => 6, 'g' => 7 },
+        'h' => 8,
+    };
+    let expected: Vec<_> =
+        tree.iter_breadth_first().map(|(_, value)| *value).collect();
+
+    for concurrency in 1..5 {
+        let lazy = LazyTree::new(0, MemoryLoader::new(tree.clone()));
+        let values: Vec<_> = block_on(
+            lazy.stream_breadth_first(concurrency)
+                .map(|node| *node.unwrap().value())
+                .collect(),
+        );
+        assert_eq!(values, expected);
+    }
+}
+
+#[test]
+fn loads_are_bounded() {
+    let tree = tree! { 0;
+        'a' => 1 { 'b' => 2 { 'c' => 3 }, 'd' => 4 },
+        'e' => 5 { 'f' => 6, 'g' => 7 },
+        'h' => 8,
+    };
+    for concurrency in 1..4 {
+        let lazy = LazyTree::new(0, MemoryLoader::new(tree.clone()));
+        block_on(lazy.stream_depth_first(concurrency).count());
+        assert_eq!(lazy.loader().max_in_flight.get(), concurrency);
+
+        let lazy = LazyTree::new(0, MemoryLoader::new(tree.clone()));
+        block_on(lazy.stream_breadth_first(concurrency).count());
+        assert_eq!(lazy.loader().max_in_flight.get(), concurrency);
+    }
+}
+
+#[test]
+fn failed_loads_are_yielded_in_place_of_children() {
+    let mut tree = tree! { 0;
+        'a' => 1 { 'b' => 2 { 'c' => 3 }, 'd' => 4 },
+        'e' => 5 { 'f' => 6, 'g' => 7 },
+        'h' => 8,
+    };
+    tree.get_child_mut(&'a').unwrap().set_value(-1);
+    let lazy = LazyTree::new(0, MemoryLoader::new(tree));
+
+    let values: Vec<_> = block_on(
+        lazy.stream_depth_first(2)
+            .map(|node| node.map(|node| *node.value()))
+            .collect(),
+    );
+    assert_eq!(
+        values,
+        [
+            Ok(-1),
+            Err("can't load ['a']".to_owned()),
+            Ok(5),
+            Ok(6),
+            Ok(7),
+            Ok(8),
+        ]
+    );
+
+    let values: Vec<_> = block_on(
+        lazy.stream_breadth_first(2)
+            .map(|node| node.map(|node| *node.value()))
+            .collect(),
+    );
+    assert_eq!(
+        values,
+        [
+            Ok(-1),
+            Ok(5),
+            Ok(8),
+            Err("can't load ['a']".to_owned()),
+            Ok(6),
+            Ok(7),
+        ]
+    );
+}
+
+#[test]
+fn loaded_children_are_kept() {
+    let tree = tree! { 0;
+        'a' => 1 { 'b' => 2 { 'c' => 3 }, 'd' => 4 },
+        'e' => 5 { 'f' => 6, 'g' => 7 },
+        'h' => 8,
+    };
+    let lazy = LazyTree::new(0, MemoryLoader::new(tree));
+    let first: Vec<_> = block_on(
+        lazy.stream_depth_first(2)
+            .map(|n| node(n.unwrap()))
+            .collect(),
+    );
+    assert_eq!(lazy.loader().loads.get(), 9);
+
+    let again: Vec<_> = block_on(
+        lazy.stream_breadth_first(2)
+            .map(|n| node(n.unwrap()))
+            .collect(),
+    );
+    assert_eq!(lazy.loader().loads.get(), 9);
+    assert_eq!(again.len(), first.len());
+    let children = lazy.loaded_children().unwrap();
+    assert!(children[&'a'].is_expanded());
+}
+
+#[test]
+#[should_panic]
+fn concurrency_must_not_be_zero() {
+    let tree = tree! { 0;
+        'a' => 1 { 'b' => 2 { 'c' => 3 }, 'd' => 4 },
+        'e' => 5 { 'f' => 6, 'g' => 7 },
+        'h' => 8,
+    };
+    let lazy = LazyTree::new(0, MemoryLoader::new(tree));
+    let _ = lazy.stream_depth_first(0);
+}
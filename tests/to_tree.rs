@@ -0,0 +1,173 @@
+#![cfg(feature = "serde")]
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tagged_tree::{from_tree, to_tree, value::Value, Tree};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+enum Mode {
+    Fast,
+    Limited(u32),
+    Range(i8, i8),
+    Custom { level: u8, name: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Config {
+    name: String,
+    ratio: f64,
+    enabled: bool,
+    initial: char,
+    data: Vec<u8>,
+    servers: Vec<Server>,
+    modes: Vec<Mode>,
+    limits: BTreeMap<u32, i64>,
+    backup: Option<Server>,
+    tags: Vec<String>,
+    pair: (bool, String),
+    unit: (),
+}
+
+fn config() -> Config {
+    let mut limits = BTreeMap::new();
+    limits.insert(10, -1);
+    limits.insert(2, 5);
+
+    Config {
+        name: "app".to_owned(),
+        ratio: 0.25,
+        enabled: true,
+        initial: 'x',
+        data: vec![1, 2],
+        servers: (0..12)
+            .map(|i| Server {
+                host: format!("host{}", i),
+                port: 8000 + i,
+            })
+            .collect(),
+        modes: vec![
+            Mode::Fast,
+            Mode::Limited(3),
+            Mode::Range(-1, 1),
+            Mode::Custom {
+                level: 2,
+                name: "c".to_owned(),
+            },
+        ],
+        limits,
+        backup: None,
+        tags: Vec::new(),
+        pair: (false, "b".to_owned()),
+        unit: (),
+    }
+}
+
+fn key(key: &str) -> String {
+    key.to_owned()
+}
+
+#[test]
+fn builds_nested_nodes() {
+    let tree = to_tree(&config()).unwrap();
+
+    assert_eq!(*tree.value(), Value::Null);
+    assert_eq!(*tree["name"].value(), Value::String(key("app")));
+    assert_eq!(*tree["ratio"].value(), Value::Float(0.25));
+    assert_eq!(*tree["initial"].value(), Value::String(key("x")));
+    assert_eq!(*tree["servers"]["11"]["port"].value(), Value::Int(8011));
+    assert_eq!(*tree["limits"]["10"].value(), Value::Int(-1));
+    assert_eq!(*tree["backup"].value(), Value::Null);
+    assert_eq!(*tree["pair"]["1"].value(), Value::String(key("b")));
+}
+
+#[test]
+fn enums_are_externally_tagged() {
+    let tree = to_tree(&config()).unwrap();
+    let modes = &tree["modes"];
+
+    assert_eq!(*modes["0"].value(), Value::String(key("Fast")));
+    assert_eq!(*modes["1"]["Limited"].value(), Value::Int(3));
+    assert_eq!(*modes["2"]["Range"]["0"].value(), Value::Int(-1));
+    assert_eq!(*modes["3"]["Custom"]["level"].value(), Value::Int(2));
+}
+
+#[test]
+fn round_trips() {
+    let tree = to_tree(&config()).unwrap();
+    assert_eq!(from_tree::<Config>(&tree).unwrap(), config());
+
+    let mut with_backup = config();
+    with_backup.backup = Some(with_backup.servers[0].clone());
+    with_backup.tags = vec![key("a")];
+    let tree = to_tree(&with_backup).unwrap();
+    assert_eq!(from_tree::<Config>(&tree).unwrap(), with_backup);
+}
+
+#[test]
+fn layers_can_be_merged() {
+    let mut base = to_tree(&config()).unwrap();
+    let mut overrides = Tree::new(Value::Null);
+    overrides
+        .entry(key("servers"))
+        .or_insert(Value::Null)
+        .entry(key("0"))
+        .or_insert(Value::Null)
+        .add_child(key("port"), Value::Int(9000));
+    overrides.add_child(key("enabled"), Value::Bool(false));
+
+    for (path, node) in overrides.iter_paths() {
+        if node.is_childless() {
+            path.iter()
+                .try_fold(&mut base, |node, key| node.get_child_mut(*key))
+                .unwrap()
+                .set_value(node.value().clone());
+        }
+    }
+
+    let merged: Config = from_tree(&base).unwrap();
+    assert_eq!(merged.servers[0].port, 9000);
+    assert!(!merged.enabled);
+    assert_eq!(merged.name, "app");
+}
+
+#[test]
+fn rejects_unrepresentable_values() {
+    assert!(to_tree(&u64::MAX).is_err());
+
+    let mut map = BTreeMap::new();
+    map.insert(vec![1], 1);
+    let err = to_tree(&map).unwrap_err();
+    assert!(err.message().starts_with("map keys must be"));
+}
+
+#[test]
+fn reports_mismatched_trees() {
+    let mut tree = to_tree(&config()).unwrap();
+    tree.get_child_mut("servers")
+        .unwrap()
+        .get_child_mut("0")
+        .unwrap()
+        .add_child(key("port"), Value::Int(-1));
+    assert!(from_tree::<Config>(&tree).is_err());
+
+    let mut tree = to_tree(&config()).unwrap();
+    tree.get_child_mut("servers").unwrap().remove("3");
+    let err = from_tree::<Config>(&tree).unwrap_err();
+    assert!(err.message().contains("keyed by the index"));
+}
+
+#[test]
+fn scalars_are_lone_roots() {
+    assert_eq!(to_tree(&5u8).unwrap(), Tree::new(Value::Int(5)));
+    assert_eq!(from_tree::<u8>(&Tree::new(Value::Int(5))).unwrap(), 5);
+    assert_eq!(
+        from_tree::<Option<u8>>(&Tree::new(Value::Null)).unwrap(),
+        None
+    );
+}
@@ -0,0 +1,113 @@
+use tagged_tree::Trie;
+
+type TestSubject = Trie<char, usize>;
+
+fn make_trie() -> TestSubject {
+    let mut trie = TestSubject::new();
+    trie.insert("to".chars(), 1);
+    trie.insert("tea".chars(), 2);
+    trie.insert("ten".chars(), 3);
+    trie.insert("inn".chars(), 4);
+    trie
+}
+
+#[test]
+fn insert_and_get() {
+    let mut trie = make_trie();
+    assert_eq!(trie.len(), 4);
+    assert_eq!(trie.get("tea".chars()), Some(&2));
+    assert_eq!(trie.get("te".chars()), None);
+    assert_eq!(trie.get("teapot".chars()), None);
+
+    assert_eq!(trie.insert("tea".chars(), 5), Some(2));
+    assert_eq!(trie.get("tea".chars()), Some(&5));
+    assert_eq!(trie.len(), 4);
+
+    *trie.get_mut("to".chars()).unwrap() += 10;
+    assert_eq!(trie.get(['t', 'o'].iter()), Some(&11));
+}
+
+#[test]
+fn empty_sequence_is_stored_in_the_root() {
+    let mut trie = TestSubject::new();
+    assert_eq!(trie.get("".chars()), None);
+    trie.insert("".chars(), 7);
+    assert_eq!(trie.get("".chars()), Some(&7));
+    assert_eq!(trie.remove("".chars()), Some(7));
+    assert!(trie.is_empty());
+}
+
+#[test]
+fn longest_prefix_match() {
+    let trie = make_trie();
+    assert_eq!(trie.longest_prefix_match("tension".chars()), Some((3, &3)));
+    assert_eq!(trie.longest_prefix_match("toast".chars()), Some((2, &1)));
+    assert_eq!(trie.longest_prefix_match("te".chars()), None);
+    assert_eq!(trie.longest_prefix_match("xyz".chars()), None);
+}
+
+#[test]
+fn iter_prefix() {
+    let trie = make_trie();
+    let found: Vec<(String, usize)> = trie
+        .iter_prefix("te".chars())
+        .map(|(seq, value)| (seq.into_iter().collect(), *value))
+        .collect();
+    assert_eq!(found, vec![("tea".to_owned(), 2), ("ten".to_owned(), 3)]);
+
+    assert_eq!(trie.iter_prefix("x".chars()).count(), 0);
+    assert_eq!(trie.iter().count(), 4);
+}
+
+#[test]
+fn starts_with() {
+    let trie = make_trie();
+    assert!(trie.starts_with("t".chars()));
+    assert!(trie.starts_with("inn".chars()));
+    assert!(!trie.starts_with("inns".chars()));
+    assert!(!trie.starts_with("a".chars()));
+    assert!(trie.starts_with("".chars()));
+}
+
+#[test]
+fn empty_trie_starts_with_nothing() {
+    let mut trie = TestSubject::new();
+    assert!(!trie.starts_with("".chars()));
+
+    trie.insert("".chars(), 1);
+    assert!(trie.starts_with("".chars()));
+}
+
+#[test]
+fn remove_prunes_empty_branches() {
+    let mut trie = make_trie();
+
+    assert_eq!(trie.remove("inn".chars()), Some(4));
+    assert!(!trie.starts_with("i".chars()));
+    assert!(!trie.as_tree().contains_key(&'i'));
+
+    assert_eq!(trie.remove("tea".chars()), Some(2));
+    assert!(!trie.starts_with("tea".chars()));
+    assert!(trie.starts_with("te".chars()));
+
+    assert_eq!(trie.remove("ten".chars()), Some(3));
+    assert!(!trie.starts_with("te".chars()));
+    assert!(trie.starts_with("t".chars()));
+
+    assert_eq!(trie.remove("ten".chars()), None);
+    assert_eq!(trie.len(), 1);
+}
+
+#[test]
+fn remove_keeps_values_along_the_path() {
+    let mut trie = TestSubject::new();
+    trie.insert("a".chars(), 1);
+    trie.insert("abc".chars(), 2);
+
+    assert_eq!(trie.remove("abc".chars()), Some(2));
+    assert_eq!(trie.get("a".chars()), Some(&1));
+    assert!(trie.as_tree()[&'a'].is_childless());
+
+    assert_eq!(trie.remove("a".chars()), Some(1));
+    assert!(trie.as_tree().is_childless());
+}
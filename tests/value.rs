@@ -0,0 +1,113 @@
+#![cfg(feature = "serde")]
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tagged_tree::{value::Value, Tree};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Document {
+    #[serde(with = "tagged_tree::value::nested")]
+    tree: Tree<String, Value>,
+}
+
+fn key(key: &str) -> String {
+    key.to_owned()
+}
+
+#[test]
+fn values_use_the_matching_data_types() {
+    let values = vec![
+        Value::Null,
+        Value::Bool(true),
+        Value::Int(-3),
+        Value::Float(0.5),
+        Value::String(key("a")),
+    ];
+    let json = serde_json::to_value(&values).unwrap();
+    assert_eq!(json, json!([null, true, -3, 0.5, "a"]));
+    assert_eq!(serde_json::from_value::<Vec<Value>>(json).unwrap(), values);
+}
+
+#[test]
+fn values_reject_large_unsigned_integers() {
+    assert!(serde_json::from_value::<Value>(json!(u64::MAX)).is_err());
+    assert!(serde_json::from_value::<Value>(json!([1])).is_err());
+}
+
+#[test]
+fn reads_nested_data() {
+    let document: Document = serde_json::from_value(json!({
+        "tree": {
+            "name": "app",
+            "server": { "port": 8080, "tls": null },
+            "ports": [80, 443],
+        }
+    }))
+    .unwrap();
+    let tree = document.tree;
+
+    assert_eq!(*tree.value(), Value::Null);
+    assert_eq!(*tree["name"].value(), Value::String(key("app")));
+    assert_eq!(*tree["server"]["port"].value(), Value::Int(8080));
+    assert_eq!(*tree["server"]["tls"].value(), Value::Null);
+    assert_eq!(*tree["ports"]["1"].value(), Value::Int(443));
+}
+
+#[test]
+fn writes_nested_data() {
+    let mut tree = Tree::new(Value::Null);
+    tree.entry(key("a"))
+        .or_insert(Value::Null)
+        .add_child(key("b"), Value::Bool(false));
+    tree.add_child(key("c"), Value::Float(1.5));
+
+    let json = serde_json::to_value(&Document { tree }).unwrap();
+    assert_eq!(json, json!({ "tree": { "a": { "b": false }, "c": 1.5 } }));
+
+    let document: Document = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&document).unwrap(), json);
+}
+
+#[test]
+fn sequences_are_written_in_index_order() {
+    let document: Document =
+        serde_json::from_value(json!({ "tree": (0..12).collect::<Vec<_>>() }))
+            .unwrap();
+    assert_eq!(*document.tree["10"].value(), Value::Int(10));
+
+    let entries: Vec<_> = (0..12).map(|i| format!(r#""{0}":{0}"#, i)).collect();
+    let expected = format!(r#"{{"tree":{{{}}}}}"#, entries.join(","));
+    assert_eq!(serde_json::to_string(&document).unwrap(), expected);
+
+    // other maps keep the order of their keys
+    let mut tree = Tree::new(Value::Null);
+    for key in ["10", "9", "09"] {
+        tree.add_child(key.to_owned(), Value::Null);
+    }
+    assert_eq!(
+        serde_json::to_string(&Document { tree }).unwrap(),
+        r#"{"tree":{"09":null,"10":null,"9":null}}"#
+    );
+}
+
+#[test]
+fn scalars_are_lone_roots() {
+    let document: Document =
+        serde_json::from_value(json!({ "tree": 5 })).unwrap();
+    assert_eq!(document.tree, Tree::new(Value::Int(5)));
+}
+
+#[test]
+fn internal_nodes_cannot_hold_values() {
+    let mut tree = Tree::new(Value::Int(1));
+    tree.add_child(key("a"), Value::Int(2));
+    let err = serde_json::to_value(&Document { tree }).unwrap_err();
+    assert!(err.to_string().contains("can only hold a null value"));
+}
+
+#[test]
+fn rejects_duplicate_keys() {
+    let err = serde_json::from_str::<Document>(r#"{"tree":{"a":1,"a":2}}"#)
+        .unwrap_err();
+    assert!(err.to_string().contains("duplicate key `a`"));
+}